@@ -0,0 +1,23 @@
+use crate::SongMetadata;
+use std::path::PathBuf;
+
+/// Reports whether an album's tracks were encoded for gapless playback, so a
+/// player can hard-join them instead of cross-fading.
+///
+/// Heuristic: every track uses the same codec (judged by the parsed
+/// [`SongMetadata::format`], not the file extension) and sample rate, and
+/// carries encoder delay/padding information (LAME header or `iTunSMPB`),
+/// which is only written by encoders that support gapless playback. The
+/// paths only identify the tracks. An empty album is not considered gapless.
+pub fn detect_album_gapless(tracks: &[(PathBuf, SongMetadata)]) -> bool {
+    let Some((_, first)) = tracks.first() else {
+        return false;
+    };
+
+    tracks.iter().all(|(_, meta)| {
+        meta.format == first.format
+            && meta.sample_rate == first.sample_rate
+            && meta.encoder_delay.is_some()
+            && meta.encoder_padding.is_some()
+    })
+}
@@ -0,0 +1,126 @@
+use crate::{scan_all, MetaError, SongMetadata};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+type Scanned = Vec<(PathBuf, Result<SongMetadata, MetaError>)>;
+
+/// A parsed file in the scan cache, valid while the file's modification
+/// time still matches `modified`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Absolute path of the file.
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
+    pub metadata: SongMetadata,
+}
+
+/// Reads the cache at `utils::SCANFILE_PATH`. A missing cache is empty.
+pub fn load_cache() -> Result<Vec<CacheEntry>, MetaError> {
+    load_cache_from(Path::new(&*utils::SCANFILE_PATH))
+}
+
+/// Writes `entries` to the cache at `utils::SCANFILE_PATH`, replacing it.
+pub fn save_cache(entries: &[(PathBuf, SongMetadata)]) -> Result<(), MetaError> {
+    save_cache_to(Path::new(&*utils::SCANFILE_PATH), entries)
+}
+
+/// Like [`load_cache`], for a cache file somewhere else.
+pub fn load_cache_from(cache: &Path) -> Result<Vec<CacheEntry>, MetaError> {
+    match fs::read(cache) {
+        Ok(json) => Ok(serde_json::from_slice(&json).map_err(io::Error::from)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Like [`save_cache`], for a cache file somewhere else. Paths are stored
+/// absolute, each with the file's current modification time.
+pub fn save_cache_to(cache: &Path, entries: &[(PathBuf, SongMetadata)]) -> Result<(), MetaError> {
+    let entries: Vec<CacheEntry> = entries
+        .iter()
+        .map(|(path, metadata)| CacheEntry {
+            path: absolute(path),
+            modified: modified(path),
+            metadata: metadata.clone(),
+        })
+        .collect();
+    write_cache(cache, &entries)
+}
+
+/// [`scan_all`] backed by the cache at `utils::SCANFILE_PATH`: files whose
+/// modification time matches their cached entry aren't parsed again. Every
+/// file that parsed is then written back, alongside the cache's entries for
+/// files that weren't part of this scan.
+pub fn scan_with_cache(paths: &[PathBuf], threads: usize) -> Result<Scanned, MetaError> {
+    scan_with_cache_in(Path::new(&*utils::SCANFILE_PATH), paths, threads)
+}
+
+/// Like [`scan_with_cache`], for a cache file somewhere else. An unreadable
+/// cache is treated as empty; failing to rewrite it is an error.
+pub fn scan_with_cache_in(cache: &Path, paths: &[PathBuf], threads: usize) -> Result<Scanned, MetaError> {
+    let mut cached: HashMap<PathBuf, CacheEntry> = load_cache_from(cache)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| (entry.path.clone(), entry))
+        .collect();
+
+    // the mtime is taken before parsing, so a file changed mid-scan is
+    // parsed again next time
+    let mut results = Vec::with_capacity(paths.len());
+    let mut stale = Vec::new();
+    for path in paths {
+        let path_abs = absolute(path);
+        let mtime = modified(path);
+        match cached.remove(&path_abs) {
+            Some(entry) if entry.modified.is_some() && entry.modified == mtime => {
+                results.push((path_abs, mtime, Some(Ok(entry.metadata))));
+            }
+            _ => {
+                results.push((path_abs, mtime, None));
+                stale.push(path.clone());
+            }
+        }
+    }
+
+    let mut parsed = scan_all(&stale, threads).into_iter();
+    let results: Vec<_> = results
+        .into_iter()
+        .map(|(path_abs, mtime, cached)| {
+            let result = cached.unwrap_or_else(|| parsed.next().expect("one result per stale path").1);
+            (path_abs, mtime, result)
+        })
+        .collect();
+
+    let mut entries: Vec<CacheEntry> = results
+        .iter()
+        .filter_map(|(path, modified, result)| {
+            Some(CacheEntry { path: path.clone(), modified: *modified, metadata: result.as_ref().ok()?.clone() })
+        })
+        .collect();
+    let mut rest: Vec<CacheEntry> = cached.into_values().collect();
+    rest.sort_by(|a, b| a.path.cmp(&b.path));
+    entries.extend(rest);
+    write_cache(cache, &entries)?;
+
+    Ok(paths.iter().zip(results).map(|(path, (_, _, result))| (path.clone(), result)).collect())
+}
+
+fn write_cache(cache: &Path, entries: &[CacheEntry]) -> Result<(), MetaError> {
+    if let Some(dir) = cache.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(cache, serde_json::to_vec(entries).map_err(io::Error::from)?)?;
+    Ok(())
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+use std::io;
+
+/// Why a file couldn't be parsed.
+#[derive(Debug)]
+pub enum MetaError {
+    /// Opening, reading or seeking the source failed.
+    Io(io::Error),
+    /// The container or codec isn't one this crate reads.
+    UnsupportedFormat,
+    /// A header or block ends before its declared length.
+    Truncated { expected: usize, got: usize },
+    /// A tag or header block is missing or malformed; names which one.
+    InvalidTag(&'static str),
+}
+
+impl fmt::Display for MetaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetaError::Io(e) => write!(f, "i/o error: {e}"),
+            MetaError::UnsupportedFormat => write!(f, "unsupported audio format"),
+            MetaError::Truncated { expected, got } => write!(f, "truncated: expected {expected} bytes, got {got}"),
+            MetaError::InvalidTag(what) => write!(f, "missing or invalid {what}"),
+        }
+    }
+}
+
+impl std::error::Error for MetaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MetaError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A damaged part of a file that [`SongMetadata::from_file_verbose`] skipped
+/// over instead of giving up on the rest of the tag.
+///
+/// [`SongMetadata::from_file_verbose`]: crate::SongMetadata::from_file_verbose
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// The frame ID as read, e.g. `APIC`; may be garbage for a corrupt header.
+    pub frame: String,
+    /// Offset of the frame header within the tag body.
+    pub offset: usize,
+    /// What was wrong with it.
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "skipped frame {:?} at offset {}: {}", self.frame, self.offset, self.reason)
+    }
+}
+
+impl From<io::Error> for MetaError {
+    fn from(e: io::Error) -> Self {
+        MetaError::Io(e)
+    }
+}
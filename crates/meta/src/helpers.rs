@@ -1,11 +1,95 @@
-use crate::SongMetadata;
+use crate::{BextMetadata, ParseWarning, SongMetadata};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::ops::Deref;
 // --- Shared helpers ---
-pub fn trim_id3v1_text(b: &[u8]) -> Option<String> {
-    let binding = String::from_utf8_lossy(b);
-    let s = binding.trim_end_matches('\u{0}').trim();
+
+/// Normalises a decoded tag value: null padding and surrounding whitespace
+/// are stripped, and a value left empty becomes `None`. Every parser goes
+/// through this so `Some("")` never reaches `SongMetadata`.
+pub fn clean_tag_text(s: &str) -> Option<String> {
+    let s = s.trim_matches(|c: char| c == '\0' || c.is_whitespace());
     if s.is_empty() { None } else { Some(s.to_string()) }
 }
 
+pub fn trim_id3v1_text(b: &[u8]) -> Option<String> {
+    clean_tag_text(&String::from_utf8_lossy(b))
+}
+
+/// The ID3v1 genre list, including the Winamp extensions (codes 0–191).
+const ID3V1_GENRES: [&str; 192] = [
+    "Blues", "Classic Rock", "Country", "Dance", "Disco", "Funk", "Grunge", "Hip-Hop", "Jazz",
+    "Metal", "New Age", "Oldies", "Other", "Pop", "R&B", "Rap", "Reggae", "Rock", "Techno",
+    "Industrial", "Alternative", "Ska", "Death Metal", "Pranks", "Soundtrack", "Euro-Techno",
+    "Ambient", "Trip-Hop", "Vocal", "Jazz+Funk", "Fusion", "Trance", "Classical",
+    "Instrumental", "Acid", "House", "Game", "Sound Clip", "Gospel", "Noise",
+    "Alternative Rock", "Bass", "Soul", "Punk", "Space", "Meditative", "Instrumental Pop",
+    "Instrumental Rock", "Ethnic", "Gothic", "Darkwave", "Techno-Industrial", "Electronic",
+    "Pop-Folk", "Eurodance", "Dream", "Southern Rock", "Comedy", "Cult", "Gangsta", "Top 40",
+    "Christian Rap", "Pop/Funk", "Jungle", "Native American", "Cabaret", "New Wave",
+    "Psychedelic", "Rave", "Showtunes", "Trailer", "Lo-Fi", "Tribal", "Acid Punk", "Acid Jazz",
+    "Polka", "Retro", "Musical", "Rock & Roll", "Hard Rock", "Folk", "Folk-Rock",
+    "National Folk", "Swing", "Fast Fusion", "Bebop", "Latin", "Revival", "Celtic", "Bluegrass",
+    "Avantgarde", "Gothic Rock", "Progressive Rock", "Psychedelic Rock", "Symphonic Rock",
+    "Slow Rock", "Big Band", "Chorus", "Easy Listening", "Acoustic", "Humour", "Speech",
+    "Chanson", "Opera", "Chamber Music", "Sonata", "Symphony", "Booty Bass", "Primus",
+    "Porn Groove", "Satire", "Slow Jam", "Club", "Tango", "Samba", "Folklore", "Ballad",
+    "Power Ballad", "Rhythmic Soul", "Freestyle", "Duet", "Punk Rock", "Drum Solo",
+    "A Cappella", "Euro-House", "Dance Hall", "Goa", "Drum & Bass", "Club-House",
+    "Hardcore Techno", "Terror", "Indie", "BritPop", "Negerpunk", "Polsk Punk", "Beat",
+    "Christian Gangsta Rap", "Heavy Metal", "Black Metal", "Crossover",
+    "Contemporary Christian", "Christian Rock", "Merengue", "Salsa", "Thrash Metal", "Anime",
+    "Jpop", "Synthpop", "Abstract", "Art Rock", "Baroque", "Bhangra", "Big Beat", "Breakbeat",
+    "Chillout", "Downtempo", "Dub", "EBM", "Eclectic", "Electro", "Electroclash", "Emo",
+    "Experimental", "Garage", "Global", "IDM", "Illbient", "Industro-Goth", "Jam Band",
+    "Krautrock", "Leftfield", "Lounge", "Math Rock", "New Romantic", "Nu-Breakz", "Post-Punk",
+    "Post-Rock", "Psytrance", "Shoegaze", "Space Rock", "Trop Rock", "World Music",
+    "Neoclassical", "Audiobook", "Audio Theatre", "Neue Deutsche Welle", "Podcast",
+    "Indie Rock", "G-Funk", "Dubstep", "Garage Rock", "Psybient",
+];
+
+/// Name of an ID3v1 genre code, or `None` for codes outside the table
+/// (255 conventionally means "no genre").
+pub fn id3v1_genre_name(code: u8) -> Option<&'static str> {
+    ID3V1_GENRES.get(code as usize).copied()
+}
+
+/// Resolves an ID3v2 `TCON` value into genre names. ID3v2.3 may reference
+/// the ID3v1 table as `"(17)"`, or use `"(RX)"` (Remix) and `"(CR)"` (Cover),
+/// optionally followed by refinement text: `"(17)Hardcore"` gives `Rock` and
+/// `Hardcore`. A doubled `"(("` escapes a literal parenthesis. Plain text
+/// passes through unchanged.
+pub fn id3v2_genres(value: String) -> Vec<String> {
+    let mut genres: Vec<String> = Vec::new();
+    let mut rest = value.as_str();
+    while let Some(inner) = rest.strip_prefix('(') {
+        if inner.starts_with('(') {
+            rest = inner;
+            break;
+        }
+        let Some((token, after)) = inner.split_once(')') else {
+            break;
+        };
+        let name = match token {
+            "RX" => "Remix",
+            "CR" => "Cover",
+            code => match code.parse::<u8>().ok().and_then(id3v1_genre_name) {
+                Some(name) => name,
+                None => break,
+            },
+        };
+        genres.push(name.to_string());
+        rest = after;
+    }
+    let refinement = rest.trim();
+    // taggers often repeat the resolved name as the refinement
+    if !refinement.is_empty() && !genres.iter().any(|g| g == refinement) {
+        genres.push(refinement.to_string());
+    }
+    genres
+}
+
 pub fn synchsafe_to_u32(bytes: &[u8]) -> u32 {
     ((bytes[0] as u32 & 0x7F) << 21)
         | ((bytes[1] as u32 & 0x7F) << 14)
@@ -13,21 +97,96 @@ pub fn synchsafe_to_u32(bytes: &[u8]) -> u32 {
         | (bytes[3] as u32 & 0x7F)
 }
 
+/// Inverse of `synchsafe_to_u32`: spreads a 28-bit value over four 7-bit bytes.
+pub fn synchsafe_from_u32(value: u32) -> [u8; 4] {
+    debug_assert!(value < 1 << 28, "synchsafe values are limited to 28 bits");
+    [
+        ((value >> 21) & 0x7F) as u8,
+        ((value >> 14) & 0x7F) as u8,
+        ((value >> 7) & 0x7F) as u8,
+        (value & 0x7F) as u8,
+    ]
+}
+
+/// Splits a decoded ID3v2.4 text frame into its null-separated values. The
+/// separator is already a single `'\0'` character here, whether the frame was
+/// UTF-8 or UTF-16. Empty values are dropped.
+pub fn split_text_values(text: Option<String>) -> Vec<String> {
+    text.map(|t| t.split('\0').filter_map(clean_tag_text).collect()).unwrap_or_default()
+}
+
+/// Decodes an ID3v2 text frame body by its leading encoding byte:
+/// 0 = Latin-1, 1 = UTF-16, 2 = UTF-16BE without BOM, 3 = UTF-8. Latin-1
+/// frames that happen to be valid UTF-8 are read as UTF-8, as many taggers
+/// write UTF-8 there regardless.
 pub fn decode_text_frame(data: &[u8]) -> Option<String> {
     if data.is_empty() { return None; }
     match data[0] {
-        0 => Some(String::from_utf8_lossy(&data[1..]).trim_matches(char::from(0)).to_string()),
-        1 => {
-            let utf16: Vec<u16> = data[1..]
-                .chunks(2)
-                .filter_map(|b| if b.len() == 2 { Some(u16::from_be_bytes([b[0], b[1]])) } else { None })
+        0 => match std::str::from_utf8(&data[1..]) {
+            Ok(text) => clean_tag_text(text),
+            Err(_) => clean_tag_text(&data[1..].iter().map(|&b| b as char).collect::<String>()),
+        },
+        3 => clean_tag_text(&String::from_utf8_lossy(&data[1..])),
+        1 | 2 => {
+            // encoding 1 starts with a byte-order mark; big-endian without one
+            let (text, little_endian) = match &data[1..] {
+                [0xFF, 0xFE, rest @ ..] if data[0] == 1 => (rest, true),
+                [0xFE, 0xFF, rest @ ..] if data[0] == 1 => (rest, false),
+                rest => (rest, false),
+            };
+            let utf16: Vec<u16> = text
+                .chunks_exact(2)
+                .map(|b| if little_endian { u16::from_le_bytes([b[0], b[1]]) } else { u16::from_be_bytes([b[0], b[1]]) })
                 .collect();
-            Some(String::from_utf16_lossy(&utf16).trim_matches(char::from(0)).to_string())
+            clean_tag_text(&String::from_utf16_lossy(&utf16))
         }
         _ => None,
     }
 }
 
+/// Reads the rating byte of an ID3v2 `POPM` frame, which follows the
+/// null-terminated e-mail of the rating user.
+pub fn parse_popm(frame: &[u8]) -> Option<u8> {
+    let email_end = frame.iter().position(|&b| b == 0)?;
+    frame.get(email_end + 1).copied()
+}
+
+/// Reads the identifier of an ID3v2 `UFID` frame whose null-terminated
+/// owner is `owner`.
+pub fn parse_ufid(frame: &[u8], owner: &str) -> Option<String> {
+    let owner_end = frame.iter().position(|&b| b == 0)?;
+    if &frame[..owner_end] != owner.as_bytes() {
+        return None;
+    }
+    musicbrainz_id(&String::from_utf8_lossy(&frame[owner_end + 1..]))
+}
+
+/// Accepts a MusicBrainz identifier. These are UUIDs (36 characters), but
+/// other formats are kept as long as the value is non-empty and at most 64
+/// characters, the `UFID` limit.
+pub fn musicbrainz_id(value: &str) -> Option<String> {
+    clean_tag_text(value).filter(|id| id.len() <= 64)
+}
+
+/// Decodes an ID3v2 `USLT` or `COMM` frame: encoding byte, 3-byte language
+/// code, a null-terminated content descriptor, then the text in the same
+/// encoding. Returns the descriptor (empty if none) and the text.
+pub fn parse_described_text(frame: &[u8]) -> Option<(String, String)> {
+    let (&encoding, rest) = frame.split_first()?;
+    let rest = rest.get(3..)?;
+    // UTF-16 descriptors end with an aligned two-byte null
+    let (descriptor_end, text_at) = if encoding == 1 || encoding == 2 {
+        let end = rest.chunks_exact(2).position(|c| c == [0, 0])? * 2;
+        (end, end + 2)
+    } else {
+        let end = rest.iter().position(|&b| b == 0)?;
+        (end, end + 1)
+    };
+    let decode = |bytes: &[u8]| decode_text_frame(&[&[encoding], bytes].concat());
+    let text = decode(&rest[text_at..])?;
+    Some((decode(&rest[..descriptor_end]).unwrap_or_default(), text))
+}
+
 pub fn parse_vorbis_comments(meta: &mut SongMetadata, data: &[u8]) {
     if data.len() < 8 { return; }
     let vendor_len = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
@@ -44,10 +203,36 @@ pub fn parse_vorbis_comments(meta: &mut SongMetadata, data: &[u8]) {
             let parts: Vec<_> = s.splitn(2, '=').collect();
             if parts.len() == 2 {
                 match parts[0].to_ascii_lowercase().as_str() {
-                    "artist" => meta.artist = Some(parts[1].to_string()),
-                    "title" => meta.title = Some(parts[1].to_string()),
-                    "album" => meta.album = Some(parts[1].to_string()),
-                    "genre" => meta.genre = Some(parts[1].to_string()),
+                    "artist" => meta.artist = clean_tag_text(parts[1]),
+                    "title" => meta.title = clean_tag_text(parts[1]),
+                    "album" => meta.album = clean_tag_text(parts[1]),
+                    "albumartist" | "album artist" => meta.album_artist = clean_tag_text(parts[1]),
+                    "composer" => meta.composer = clean_tag_text(parts[1]),
+                    "titlesort" => meta.title_sort = clean_tag_text(parts[1]),
+                    "artistsort" => meta.artist_sort = clean_tag_text(parts[1]),
+                    "albumsort" => meta.album_sort = clean_tag_text(parts[1]),
+                    "comment" | "description" => meta.comment = clean_tag_text(parts[1]),
+                    "encoder" | "encoded_by" => meta.encoder = clean_tag_text(parts[1]),
+                    "compilation" => meta.compilation = parse_flag(parts[1]),
+                    "genre" => meta.genre = clean_tag_text(parts[1]),
+                    "tracknumber" => {
+                        let (number, total) = parse_number_pair(parts[1]);
+                        meta.track_number = number;
+                        meta.track_total = total.or(meta.track_total);
+                    }
+                    "date" | "year" => meta.year = parse_year(parts[1]),
+                    "tracktotal" | "totaltracks" => meta.track_total = parse_number_pair(parts[1]).0,
+                    "discnumber" => {
+                        let (number, total) = parse_number_pair(parts[1]);
+                        meta.disc_number = number;
+                        meta.disc_total = total.or(meta.disc_total);
+                    }
+                    "disctotal" | "totaldiscs" => meta.disc_total = parse_number_pair(parts[1]).0,
+                    "musicbrainz_trackid" => meta.musicbrainz_track_id = musicbrainz_id(parts[1]),
+                    "musicbrainz_albumid" => meta.musicbrainz_album_id = musicbrainz_id(parts[1]),
+                    "lyrics" | "unsyncedlyrics" => meta.lyrics = clean_tag_text(parts[1]),
+                    "replaygain_track_gain" => meta.replay_gain_track_db = parse_gain_db(parts[1]),
+                    "replaygain_album_gain" => meta.replay_gain_album_db = parse_gain_db(parts[1]),
                     _ => {}
                 }
             }
@@ -57,20 +242,1023 @@ pub fn parse_vorbis_comments(meta: &mut SongMetadata, data: &[u8]) {
 }
 
 pub fn extract_m4a_text(data: &[u8]) -> Option<String> {
+    let (_, body) = m4a_atoms(data).find(|(kind, _)| *kind == b"data")?;
+    // the type and locale words precede the text
+    let text = String::from_utf8_lossy(body.get(8..).unwrap_or(body));
+    clean_tag_text(&text)
+}
+
+
+/// Reads a fixed-size ASCII field: text ends at the first null, and the
+/// space padding is trimmed.
+pub fn fixed_ascii_text(b: &[u8]) -> Option<String> {
+    let end = b.iter().position(|&c| c == 0).unwrap_or(b.len());
+    clean_tag_text(&String::from_utf8_lossy(&b[..end]))
+}
+
+/// Parses a BWF `bext` chunk body. Layout: Description (256), Originator
+/// (32), OriginatorReference (32), OriginationDate (10), OriginationTime (8),
+/// TimeReference (u64 LE), then version, UMID and coding history.
+pub fn parse_bext(data: &[u8]) -> Option<BextMetadata> {
+    if data.len() < 346 {
+        return None;
+    }
+    Some(BextMetadata {
+        description: fixed_ascii_text(&data[0..256]),
+        originator: fixed_ascii_text(&data[256..288]),
+        origination_date: fixed_ascii_text(&data[320..330]),
+        origination_time: fixed_ascii_text(&data[330..338]),
+        time_reference: u64::from_le_bytes(data[338..346].try_into().unwrap()),
+    })
+}
+
+/// Reads the binary `data` payload of a `trkn`/`disk` atom: after the type and
+/// locale words come 16-bit big-endian values for padding, number and total.
+/// Zero means "not set".
+pub fn extract_m4a_number_pair(data: &[u8]) -> (Option<u32>, Option<u32>) {
+    let payload = m4a_atoms(data)
+        .find(|(kind, _)| *kind == b"data")
+        .map(|(_, body)| body.get(8..).unwrap_or(&[]))
+        .unwrap_or(&[]);
+    let word = |index: usize| {
+        payload
+            .get(index * 2..index * 2 + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]) as u32)
+            .filter(|&n| n != 0)
+    };
+    (word(1), word(2))
+}
+
+/// Reads the integer payload of an atom's `data` child (type 21, 1 to 8
+/// bytes big-endian), as used by `rtng` and the other numeric items.
+pub fn extract_m4a_integer(data: &[u8]) -> Option<u64> {
+    let (_, body) = m4a_atoms(data).find(|(kind, _)| *kind == b"data")?;
+    let payload = body.get(8..).filter(|p| (1..=8).contains(&p.len()))?;
+    Some(payload.iter().fold(0u64, |n, &b| n << 8 | b as u64))
+}
+
+/// Reads an iTunes freeform (`----`) atom body, returning its `name` key and
+/// `data` value. The `mean` namespace (usually `com.apple.iTunes`) is ignored.
+pub fn extract_m4a_freeform(data: &[u8]) -> Option<(String, String)> {
+    // `name` is a full box: 4 bytes of version/flags precede the string
+    let name = m4a_atoms(data)
+        .filter(|(kind, body)| *kind == b"name" && body.len() >= 4)
+        .last()
+        .map(|(_, body)| String::from_utf8_lossy(&body[4..]).to_string());
+    Some((name?, extract_m4a_text(data)?))
+}
+
+/// Parses a position like `"3"` or `"3/12"` (ID3 `TRCK`, Vorbis
+/// `TRACKNUMBER`). Each side that isn't a plain number, e.g. the missing
+/// total in `"3/"`, comes back as `None`.
+pub fn parse_number_pair(s: &str) -> (Option<u32>, Option<u32>) {
+    let mut parts = s.splitn(2, '/');
+    let mut next = || parts.next().and_then(|p| p.trim().trim_matches('\0').parse().ok());
+    (next(), next())
+}
+
+/// Reads a numeric boolean tag such as `TCMP`: `"0"` is false, any other
+/// number true, and anything else unknown.
+pub fn parse_flag(s: &str) -> Option<bool> {
+    s.trim().trim_matches('\0').parse::<u64>().ok().map(|n| n != 0)
+}
+
+/// Extracts the year from a date such as `"1999"` or `"2021-06-15"`:
+/// the value must start with four digits.
+pub fn parse_year(s: &str) -> Option<i32> {
+    let s = s.trim();
+    let digits = s.get(0..4)?;
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Parses a ReplayGain value such as `"-6.48 dB"` into decibels.
+pub fn parse_gain_db(s: &str) -> Option<f32> {
+    let s = s.trim();
+    let number = s
+        .strip_suffix("dB")
+        .or_else(|| s.strip_suffix("db"))
+        .or_else(|| s.strip_suffix("DB"))
+        .unwrap_or(s);
+    number.trim().parse::<f32>().ok().filter(|v| v.is_finite())
+}
+
+/// Converts an iTunes Sound Check (`iTunNORM`) string into a gain in dB.
+///
+/// The value is ten space-separated 32-bit hex numbers. The first pair holds
+/// the left/right loudness relative to a 1/1000 W reference; the louder of
+/// the two gives the gain as `-10 * log10(value / 1000)`. The remaining pairs
+/// (2500 W reference, recommended gain adjustment, peaks, etc.) are ignored.
+pub fn parse_itunnorm(s: &str) -> Option<f32> {
+    let values: Vec<u32> = s
+        .split_whitespace()
+        .map(|v| u32::from_str_radix(v, 16))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    if values.len() < 2 {
+        return None;
+    }
+    let loudest = values[0].max(values[1]);
+    if loudest == 0 {
+        return None;
+    }
+    Some((-10.0 * (loudest as f64 / 1000.0).log10()) as f32)
+}
+
+/// Frame size from an ID3v2 frame header: plain big-endian in v2.3,
+/// synchsafe in v2.4.
+pub fn id3v2_frame_size(bytes: &[u8], major: u8) -> u32 {
+    if major >= 4 {
+        synchsafe_to_u32(bytes)
+    } else {
+        u32::from_be_bytes(bytes[0..4].try_into().unwrap())
+    }
+}
+
+/// Total length of an ID3v2 extended header from its leading size field. The
+/// v2.3 size is plain and excludes the field itself; the v2.4 size is
+/// synchsafe and covers the whole header.
+pub fn id3v2_extended_header_len(size: &[u8], major: u8) -> usize {
+    if major >= 4 {
+        synchsafe_to_u32(size) as usize
+    } else {
+        4 + u32::from_be_bytes(size[0..4].try_into().unwrap()) as usize
+    }
+}
+
+/// Undoes ID3v2 unsynchronisation: every `0xFF 0x00` pair becomes `0xFF`.
+pub fn remove_unsynchronisation(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut prev = 0u8;
+    for &b in data {
+        if !(prev == 0xFF && b == 0x00) {
+            out.push(b);
+        }
+        prev = b;
+    }
+    out
+}
+
+/// The plain body of an ID3v2 frame, given its format flags (the second
+/// flag byte) and the tag's header flags. A group byte is skipped, and for
+/// ID3v2.4 per-frame unsynchronisation is undone and a data length
+/// indicator stripped. Compressed or encrypted frames yield `None`.
+pub fn id3v2_frame_body(frame: &[u8], major: u8, format: u8, tag_flags: u8) -> Option<Cow<'_, [u8]>> {
+    let (grouped, packed) = match major {
+        3 => (format & 0x20 != 0, format & 0xC0 != 0),
+        4 => (format & 0x40 != 0, format & 0x0C != 0),
+        _ => (false, false),
+    };
+    if packed {
+        return None;
+    }
+    let frame = if grouped { frame.get(1..)? } else { frame };
+    if major < 4 {
+        return Some(Cow::Borrowed(frame));
+    }
+    // ID3v2.4 unsynchronises each frame body rather than the whole tag
+    let mut body = if format & 0x02 != 0 || tag_flags & 0x80 != 0 {
+        Cow::Owned(remove_unsynchronisation(frame))
+    } else {
+        Cow::Borrowed(frame)
+    };
+    if format & 0x01 != 0 {
+        body = match body {
+            Cow::Borrowed(b) => Cow::Borrowed(b.get(4..)?),
+            Cow::Owned(b) => Cow::Owned(b.get(4..)?.to_vec()),
+        };
+    }
+    Some(body)
+}
+
+/// The ID3v2.3 equivalent of an ID3v2.2 frame ID, for the frames we read.
+fn v22_frame_id(id: &[u8]) -> Option<&'static [u8]> {
+    let v23: &[u8] = match id {
+        b"TT2" => b"TIT2",
+        b"TP1" => b"TPE1",
+        b"TP2" => b"TPE2",
+        b"TAL" => b"TALB",
+        b"TCO" => b"TCON",
+        b"TYE" => b"TYER",
+        b"TRK" => b"TRCK",
+        b"ULT" => b"USLT",
+        _ => return None,
+    };
+    Some(v23)
+}
+
+/// The ID3v2.4 ID for a frame carried over from an ID3v2.3 tag, or `None`
+/// for the ID3v2.3-only frames that ID3v2.4 replaced with ones of a
+/// different shape.
+pub fn v23_to_v24_frame_id(id: &[u8]) -> Option<&[u8]> {
+    match id {
+        b"TYER" => Some(b"TDRC"),
+        b"TORY" => Some(b"TDOR"),
+        b"TDAT" | b"TIME" | b"TRDA" | b"TSIZ" | b"IPLS" => None,
+        _ => Some(id),
+    }
+}
+
+/// Walks ID3v2 frames in a tag body, yielding `(frame_id, flags,
+/// frame_body)`; the two flag bytes are always zero for ID3v2.2, which has
+/// none. ID3v2.2 frames (6-byte headers, 3-character IDs) are reported under
+/// their ID3v2.3 IDs so callers can match a single set of names. Stops at the
+/// first padding byte or a frame that overruns the tag.
+pub fn id3v2_frames_with_flags(tag: &[u8], major: u8) -> impl Iterator<Item = (&[u8], [u8; 2], &[u8])> {
+    id3v2_frames_recovering(tag, major, None)
+}
+
+/// Like `id3v2_frames_with_flags`, but with `warnings` a damaged frame (an
+/// empty one, a bad ID, or a size past the end of the tag) is recorded and
+/// the walk resumes at the next plausible frame header instead of stopping.
+pub fn id3v2_frames_recovering<'a>(
+    tag: &'a [u8],
+    major: u8,
+    mut warnings: Option<&mut Vec<ParseWarning>>,
+) -> impl Iterator<Item = (&'a [u8], [u8; 2], &'a [u8])> {
+    let (id_len, header_len) = if major == 2 { (3, 6) } else { (4, 10) };
+    let valid_id = |id: &[u8]| id.iter().all(|&b| b.is_ascii_uppercase() || b.is_ascii_digit());
+    let frame_size = move |i: usize| {
+        if major == 2 {
+            u32::from_be_bytes([0, tag[i + 3], tag[i + 4], tag[i + 5]]) as usize
+        } else {
+            id3v2_frame_size(&tag[i + 4..i + 8], major) as usize
+        }
+    };
     let mut i = 0;
-    while i + 8 <= data.len() {
-        let size = u32::from_be_bytes(data[i..i + 4].try_into().unwrap()) as usize;
-        if size < 8 || i + size > data.len() {
+    std::iter::from_fn(move || loop {
+        if i + header_len > tag.len() {
+            return None;
+        }
+        let id = &tag[i..i + id_len];
+        let size = frame_size(i);
+        let fits = i + header_len + size <= tag.len();
+        if size != 0 && fits && (warnings.is_none() || valid_id(id)) {
+            let flags = if major == 2 { [0, 0] } else { [tag[i + 8], tag[i + 9]] };
+            let frame = &tag[i + header_len..i + header_len + size];
+            i += header_len + size;
+            let id = if major == 2 { v22_frame_id(id).unwrap_or(id) } else { id };
+            return Some((id, flags, frame));
+        }
+        // padding: the rest of the tag is zeros
+        let warnings = warnings.as_deref_mut().filter(|_| tag[i] != 0)?;
+        let reason = if !valid_id(id) {
+            "invalid frame ID"
+        } else if size == 0 {
+            "empty frame"
+        } else {
+            "frame size exceeds the tag"
+        };
+        warnings.push(ParseWarning { frame: String::from_utf8_lossy(id).into_owned(), offset: i, reason });
+        // resync on the next header with a valid ID and a size that fits
+        i = (i + 1..tag.len().saturating_sub(header_len - 1)).find(|&j| {
+            let size = frame_size(j);
+            valid_id(&tag[j..j + id_len]) && size != 0 && j + header_len + size <= tag.len()
+        })?;
+    })
+}
+
+/// An ID3v2.4 frame with cleared flags; the size is synchsafe.
+pub fn id3v24_frame(id: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend_from_slice(&synchsafe_from_u32(body.len() as u32));
+    out.extend_from_slice(&[0, 0]);
+    out.extend_from_slice(body);
+    out
+}
+
+/// An ID3v2.4 UTF-8 text frame; several values are null-separated.
+pub fn id3v24_text_frame(id: &[u8], values: &[&str]) -> Vec<u8> {
+    let mut body = vec![3u8];
+    body.extend_from_slice(values.join("\0").as_bytes());
+    id3v24_frame(id, &body)
+}
+
+/// Converts an 80-bit IEEE 754 extended float (AIFF `COMM` sample rate).
+pub fn extended_to_f64(bytes: [u8; 10]) -> f64 {
+    let sign = if bytes[0] & 0x80 != 0 { -1.0 } else { 1.0 };
+    let exponent = (u16::from_be_bytes([bytes[0], bytes[1]]) & 0x7FFF) as i32;
+    let mantissa = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+    if exponent == 0 && mantissa == 0 {
+        return 0.0;
+    }
+    sign * mantissa as f64 * 2f64.powi(exponent - 16383 - 63)
+}
+
+/// Most RIFF/IFF chunks walked before giving up, so a file made of
+/// thousands of tiny chunks can't keep a parser busy. Real files have a
+/// handful.
+pub const IFF_MAX_CHUNKS: usize = 1024;
+
+/// Largest possible Ogg page: 27-byte header, 255 lacing values and 255
+/// segments of 255 bytes.
+pub const OGG_MAX_PAGE: usize = 27 + 255 + 255 * 255;
+
+/// One Ogg page: its granule position, stream serial number, lacing values
+/// and body.
+pub struct OggPage<'a> {
+    pub granule: u64,
+    pub serial: u32,
+    pub lacing: &'a [u8],
+    pub body: &'a [u8],
+}
+
+/// Parses the Ogg page starting at `data[0]`, returning it with its total
+/// length. `None` if the capture pattern is missing or the page is truncated.
+pub fn ogg_page(data: &[u8]) -> Option<(OggPage<'_>, usize)> {
+    if data.len() < 27 || &data[0..4] != b"OggS" {
+        return None;
+    }
+    let segments = data[26] as usize;
+    let lacing = data.get(27..27 + segments)?;
+    let body_len: usize = lacing.iter().map(|&l| l as usize).sum();
+    let body = data.get(27 + segments..27 + segments + body_len)?;
+    let page = OggPage {
+        granule: u64::from_le_bytes(data[6..14].try_into().unwrap()),
+        serial: u32::from_le_bytes(data[14..18].try_into().unwrap()),
+        lacing,
+        body,
+    };
+    Some((page, 27 + segments + body_len))
+}
+
+/// Reassembles the first `count` packets of the first logical stream, reading
+/// page by page so only the pages that carry them are loaded. Packets may
+/// span pages; a lacing value below 255 ends a packet. Stops early at the end
+/// of the stream or a damaged page.
+pub fn ogg_packets<R: Read>(r: &mut R, count: usize) -> io::Result<Vec<Vec<u8>>> {
+    let mut packets = Vec::new();
+    let mut current = Vec::new();
+    let mut serial = None;
+    let mut page = Vec::with_capacity(OGG_MAX_PAGE);
+    while packets.len() < count {
+        // the fixed header, then the lacing values, then the body they sum to
+        page.clear();
+        if r.by_ref().take(27).read_to_end(&mut page)? < 27 || &page[0..4] != b"OggS" {
             break;
         }
-        if &data[i + 4..i + 8] == b"data" {
-            // skip possible data header: often 8 (data header) + 8 (meta) => text starts at i+16
-            let start = if i + 16 <= i + size { i + 16 } else { i + 8 };
-            let text = String::from_utf8_lossy(&data[start..i + size]);
-            return Some(text.trim_matches(char::from(0)).to_string());
+        let segments = page[26] as usize;
+        if r.by_ref().take(segments as u64).read_to_end(&mut page)? < segments {
+            break;
+        }
+        let body_len: u64 = page[27..].iter().map(|&l| l as u64).sum();
+        if r.by_ref().take(body_len).read_to_end(&mut page)? < body_len as usize {
+            break;
+        }
+        let Some((page, _)) = ogg_page(&page) else {
+            break;
+        };
+        if *serial.get_or_insert(page.serial) != page.serial {
+            continue;
+        }
+        let mut at = 0;
+        for &l in page.lacing {
+            current.extend_from_slice(&page.body[at..at + l as usize]);
+            at += l as usize;
+            if l < 255 {
+                packets.push(std::mem::take(&mut current));
+                if packets.len() == count {
+                    break;
+                }
+            }
         }
+    }
+    Ok(packets)
+}
+
+/// Granule position of the last complete page of stream `serial` in `tail`
+/// (the end of a file). Pages with no finished packet (granule -1) are passed
+/// over.
+pub fn ogg_last_granule(tail: &[u8], serial: u32) -> Option<u64> {
+    let mut end = tail.len();
+    while let Some(at) = tail[..end].windows(4).rposition(|w| w == b"OggS") {
+        if let Some((page, _)) = ogg_page(&tail[at..])
+            && page.serial == serial
+            && page.granule != u64::MAX
+        {
+            return Some(page.granule);
+        }
+        end = at;
+    }
+    None
+}
+
+/// Iterates over sibling MP4 atoms, yielding `(atom_type, atom_body)`.
+/// A 32-bit size of 1 means a 64-bit size follows the type (files over
+/// 4 GiB), and 0 means the atom runs to the end of `data`.
+pub fn m4a_atoms(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        if i + 8 > data.len() {
+            return None;
+        }
+        let (header, size) = match u32::from_be_bytes(data[i..i + 4].try_into().unwrap()) {
+            0 => (8, (data.len() - i) as u64),
+            1 => (16, u64::from_be_bytes(data.get(i + 8..i + 16)?.try_into().unwrap())),
+            size => (8, size as u64),
+        };
+        if size < header as u64 || size > (data.len() - i) as u64 {
+            return None;
+        }
+        let size = size as usize;
+        let atom = (&data[i + 4..i + 8], &data[i + header..i + size]);
         i += size;
+        Some(atom)
+    })
+}
+
+/// Like `m4a_atoms`, over the atoms between `start` and `end` of a seekable
+/// stream: only the headers are read, yielding `(atom_type, body_offset,
+/// body_len)`. Stops at an atom that overruns `end`.
+pub fn m4a_atom_headers<R: Read + Seek>(f: &mut R, start: u64, end: u64) -> io::Result<Vec<([u8; 4], u64, u64)>> {
+    let mut atoms = Vec::new();
+    let mut at = start;
+    while at + 8 <= end {
+        let mut header = [0u8; 16];
+        f.seek(SeekFrom::Start(at))?;
+        f.read_exact(&mut header[..8])?;
+        let (header_len, size) = match u32::from_be_bytes(header[0..4].try_into().unwrap()) {
+            0 => (8, end - at),
+            1 if at + 16 <= end => {
+                f.read_exact(&mut header[8..16])?;
+                (16, u64::from_be_bytes(header[8..16].try_into().unwrap()))
+            }
+            1 => break,
+            size => (8, size as u64),
+        };
+        if size < header_len || size > end - at {
+            break;
+        }
+        atoms.push((header[4..8].try_into().unwrap(), at + header_len, size - header_len));
+        at += size;
+    }
+    Ok(atoms)
+}
+
+/// Depth-first search for the body of the first `kind` atom, descending
+/// through the standard `moov/udta/meta/ilst` containers and the
+/// `trak/mdia/minf/stbl/stsd/mp4a` sample description path.
+pub fn find_m4a_atom<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    for (atom, body) in m4a_atoms(data) {
+        if atom == kind {
+            return Some(body);
+        }
+        let children = match atom {
+            b"moov" | b"udta" | b"ilst" | b"trak" | b"mdia" | b"minf" | b"stbl" => body,
+            // `meta` is a full box: 4 bytes of version/flags precede its children
+            b"meta" if body.len() >= 4 => &body[4..],
+            // version/flags and an entry count precede the sample entries
+            b"stsd" if body.len() >= 8 => &body[8..],
+            // the audio sample entry's fixed fields precede `esds`
+            b"mp4a" if body.len() >= 28 => &body[28..],
+            _ => continue,
+        };
+        if let Some(found) = find_m4a_atom(children, kind) {
+            return Some(found);
+        }
     }
     None
 }
 
+/// Length in bytes of the Layer III frame with this 4-byte `header`, or
+/// `None` when the header is invalid. Free-format headers (bitrate index 0)
+/// take `free_len`, the unpadded length measured from the stream.
+pub fn mp3_frame_len(header: &[u8], free_len: Option<usize>) -> Option<usize> {
+    const MPEG1_KBPS: [u32; 16] = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 0];
+    const MPEG2_KBPS: [u32; 16] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160, 0];
+    // 3 is MPEG 1, 2 MPEG 2 and 0 MPEG 2.5, each halving the sample rates
+    let version = (header[1] >> 3) & 0x03;
+    let layer = (header[1] >> 1) & 0x03;
+    let rate_index = ((header[2] >> 2) & 0x03) as usize;
+    if version == 1 || layer != 1 || rate_index == 3 {
+        return None;
+    }
+    let mpeg1 = version == 3;
+    let sample_rate = [44_100u32, 48_000, 32_000][rate_index] >> [2, 0, 1, 0][version as usize];
+    let padding = ((header[2] >> 1) & 1) as usize;
+    let bitrate_index = (header[2] >> 4) as usize;
+    if bitrate_index == 0 {
+        return free_len.map(|len| len + padding);
+    }
+    let kbps = if mpeg1 { MPEG1_KBPS } else { MPEG2_KBPS }[bitrate_index];
+    let factor = if mpeg1 { 144_000 } else { 72_000 };
+    (kbps > 0).then(|| (factor * kbps / sample_rate) as usize + padding)
+}
+
+/// Whether a tag starts at `rest` and runs to the end of the stream, which
+/// is `to_eof` bytes away, or up to a final 128-byte ID3v1 tag: ID3v1
+/// itself, an appended ID3v2 tag or its `3DI` footer, or an APE tag.
+fn trailing_tag_at(rest: &[u8], to_eof: u64) -> bool {
+    let runs_to_end = |len: u64| len == to_eof || len + 128 == to_eof;
+    if rest.starts_with(b"TAG") {
+        to_eof == 128
+    } else if rest.starts_with(b"3DI") {
+        runs_to_end(10)
+    } else if rest.len() >= 10 && rest.starts_with(b"ID3") {
+        let footer = if rest[5] & 0x10 != 0 { 10 } else { 0 };
+        runs_to_end(10 + synchsafe_to_u32(&rest[6..10]) as u64 + footer)
+    } else if rest.len() >= 32 && rest.starts_with(b"APETAGEX") {
+        // the size covers the items and footer, not this 32-byte header
+        runs_to_end(32 + u32::from_le_bytes(rest[12..16].try_into().unwrap()) as u64)
+    } else {
+        false
+    }
+}
+
+/// Whether the MPEG stream plausibly goes on after a frame with `header`
+/// that ends at `at` in `all`, with `to_eof` stream bytes left from there:
+/// two more frames with the same version, layer and sample rate follow back
+/// to back, unless the stream or a trailing tag ends it first. `free_len` is
+/// the measured free-format frame length, if any.
+pub fn mp3_frames_follow(all: &[u8], mut at: usize, header: &[u8], free_len: Option<usize>, mut to_eof: u64) -> bool {
+    for _ in 0..2 {
+        let rest = all.get(at..).unwrap_or_default();
+        if to_eof == 0 || trailing_tag_at(rest, to_eof) {
+            return true;
+        }
+        let Some(next) = rest.get(..4) else {
+            return false;
+        };
+        if next[0] != 0xFF || next[1] != header[1] || next[2] & 0x0C != header[2] & 0x0C {
+            return false;
+        }
+        let Some(len) = mp3_frame_len(next, free_len) else {
+            return false;
+        };
+        at += len;
+        to_eof = to_eof.saturating_sub(len as u64);
+    }
+    true
+}
+
+/// Unpadded length of the free-format (bitrate index 0) MPEG frame at `pos`:
+/// the distance to the next header with the same version, layer, bitrate
+/// index and sample rate, less this frame's padding byte. `None` when no
+/// such header follows within `max_len` bytes.
+pub fn mp3_free_format_len(all: &[u8], pos: usize, max_len: usize) -> Option<usize> {
+    let header = all.get(pos..pos + 4)?;
+    let padding = ((header[2] >> 1) & 1) as usize;
+    let end = all.len().min(pos + max_len + padding + 3);
+    let next = all.get(pos + 4..end)?.windows(3).position(|w| {
+        w[0] == 0xFF && w[1] == header[1] && w[2] & 0xFC == header[2] & 0xFC
+    })?;
+    Some(next + 4 - padding)
+}
+
+/// The `mdhd` body of the first audio track (`hdlr` type `soun`), else of
+/// the first track.
+pub fn find_m4a_audio_mdhd(data: &[u8]) -> Option<&[u8]> {
+    let moov = find_m4a_atom(data, b"moov")?;
+    let tracks: Vec<&[u8]> = m4a_atoms(moov).filter(|(kind, _)| *kind == b"trak").map(|(_, body)| body).collect();
+    // `hdlr`: version/flags and a predefined word precede the handler type
+    let is_audio = |trak: &&[u8]| find_m4a_atom(trak, b"hdlr").and_then(|h| h.get(8..12)) == Some(&b"soun"[..]);
+    let trak = tracks.iter().copied().find(is_audio).or(tracks.first().copied())?;
+    find_m4a_atom(trak, b"mdhd")
+}
+
+/// How much of an oversized picture frame is read to recover its MIME type,
+/// picture type and description.
+pub const PICTURE_HEADER_PEEK: usize = 4096;
+
+/// An embedded picture found in a tag.
+pub struct Picture {
+    pub mime: String,
+    /// ID3v2/FLAC picture type (3 = front cover).
+    pub kind: u8,
+    pub description: String,
+    /// Width and height in pixels, when the container records them.
+    pub dimensions: Option<(u32, u32)>,
+    /// Declared image size in bytes, known even when the bytes were skipped.
+    pub size: usize,
+    /// Image bytes; empty when the image exceeded the size cap.
+    pub data: Vec<u8>,
+}
+
+impl Picture {
+    /// Builds a picture from the image bytes available in `available`, keeping
+    /// them only when the whole declared image is present and within the cap.
+    fn new(mime: String, kind: u8, description: String, size: usize, available: &[u8], max_bytes: usize) -> Self {
+        let data = if size <= max_bytes && available.len() >= size {
+            available[..size].to_vec()
+        } else {
+            Vec::new()
+        };
+        Picture { mime, kind, description, dimensions: None, size, data }
+    }
+
+    /// Whether the image bytes were left unread because of the size cap.
+    pub fn is_skipped(&self) -> bool {
+        self.data.is_empty() && self.size > 0
+    }
+}
+
+/// Picks the front cover (picture type 3) when present, else the first picture.
+pub fn preferred_picture(pictures: Vec<Picture>) -> Option<Picture> {
+    let front = pictures.iter().position(|p| p.kind == 3).unwrap_or(0);
+    pictures.into_iter().nth(front)
+}
+
+/// Parses an ID3v2 `APIC` frame: encoding, MIME, picture type, description,
+/// image. `frame` may be just the start of a `frame_len`-byte frame.
+pub fn parse_apic(frame: &[u8], frame_len: usize, max_bytes: usize) -> Option<Picture> {
+    let encoding = *frame.first()?;
+    let rest = &frame[1..];
+    let mime_end = rest.iter().position(|&b| b == 0)?;
+    let mime = String::from_utf8_lossy(&rest[..mime_end]).to_string();
+    let kind = *rest.get(mime_end + 1)?;
+    let desc = rest.get(mime_end + 2..)?;
+
+    // the description terminator is one null byte, or two for UTF-16
+    let data_start = if encoding == 1 || encoding == 2 {
+        desc.chunks(2).position(|c| c == [0, 0]).map(|p| p * 2 + 2)?
+    } else {
+        desc.iter().position(|&b| b == 0)? + 1
+    };
+    let description = decode_text_frame(&[&[encoding], &desc[..data_start]].concat())
+        .unwrap_or_else(|| String::from_utf8_lossy(&desc[..data_start - 1]).to_string());
+    let header_len = 1 + mime_end + 2 + data_start;
+    let size = frame_len.checked_sub(header_len)?;
+    Some(Picture::new(mime, kind, description, size, &frame[header_len..], max_bytes))
+}
+
+/// Parses a FLAC `PICTURE` metadata block, which may be truncated to its
+/// leading fields when the image is too large to load.
+pub fn parse_flac_picture(block: &[u8], max_bytes: usize) -> Option<Picture> {
+    let read_u32 = |at: usize| -> Option<usize> {
+        Some(u32::from_be_bytes(block.get(at..at + 4)?.try_into().unwrap()) as usize)
+    };
+    let kind = read_u32(0)?;
+    let mime_len = read_u32(4)?;
+    let mime = String::from_utf8_lossy(block.get(8..8 + mime_len)?).to_string();
+    let desc_len = read_u32(8 + mime_len)?;
+    let description = String::from_utf8_lossy(block.get(12 + mime_len..12 + mime_len + desc_len)?).to_string();
+    // width/height/depth/colors follow the description; 0 means unknown
+    let fields_at = 12 + mime_len + desc_len;
+    let (width, height) = (read_u32(fields_at)?, read_u32(fields_at + 4)?);
+    let data_len_at = fields_at + 16;
+    let size = read_u32(data_len_at)?;
+    let available = block.get(data_len_at + 4..).unwrap_or(&[]);
+    let mut picture = Picture::new(mime, kind.min(u8::MAX as usize) as u8, description, size, available, max_bytes);
+    if width > 0 && height > 0 {
+        picture.dimensions = Some((width as u32, height as u32));
+    }
+    Some(picture)
+}
+
+/// Parses the body of a `data` atom inside `covr`. The type flag says whether
+/// the image is JPEG (13), PNG (14) or BMP (27); some taggers write the
+/// implicit type 0, in which case the format is sniffed from the image itself.
+/// MP4 has no picture types, so the caller passes `kind` by position (front
+/// cover first, by convention). `body` may be just the start of a
+/// `body_len`-byte atom body.
+pub fn parse_m4a_cover_data(body: &[u8], body_len: usize, kind: u8, max_bytes: usize) -> Option<Picture> {
+    if body.len() <= 8 || body_len < body.len() {
+        return None;
+    }
+    let type_flag = u32::from_be_bytes(body[0..4].try_into().unwrap()) & 0x00FF_FFFF;
+    let image = &body[8..];
+    let mime = match type_flag {
+        13 => "image/jpeg",
+        14 => "image/png",
+        27 => "image/bmp",
+        0 if image.starts_with(b"\xFF\xD8\xFF") => "image/jpeg",
+        0 if image.starts_with(b"\x89PNG") => "image/png",
+        0 if image.starts_with(b"BM") => "image/bmp",
+        _ => return None,
+    };
+    Some(Picture::new(mime.to_string(), kind, String::new(), body_len - 8, image, max_bytes))
+}
+
+/// Length of the Layer III side information following an MPEG audio frame
+/// header (and its CRC, if any): depends on the MPEG version and whether the
+/// frame is mono.
+pub fn mpeg_side_info_len(header: &[u8]) -> usize {
+    let mpeg1 = (header[1] >> 3) & 0x03 == 3;
+    let mono = (header[3] >> 6) & 0x03 == 3;
+    match (mpeg1, mono) {
+        (true, true) => 17,
+        (true, false) => 32,
+        (false, true) => 9,
+        (false, false) => 17,
+    }
+}
+
+/// Frame count from a Xing (VBR) or Info (CBR) header in the first MPEG
+/// audio frame, which LAME and most encoders write in place of audio data.
+/// `None` when the frame has no such header or its frame-count field is
+/// absent.
+pub fn xing_frame_count(frame: &[u8]) -> Option<u32> {
+    let (at, flags) = xing_header(frame)?;
+    if flags & 0x01 == 0 {
+        return None;
+    }
+    Some(u32::from_be_bytes(frame.get(at + 8..at + 12)?.try_into().unwrap()))
+}
+
+/// Offset and flags of a Xing/Info header, which follows the frame header,
+/// the optional CRC and the side information.
+fn xing_header(frame: &[u8]) -> Option<(usize, u32)> {
+    let crc_len = if frame.get(1)? & 0x01 == 0 { 2 } else { 0 };
+    let at = 4 + crc_len + mpeg_side_info_len(frame);
+    let tag = frame.get(at..at + 4)?;
+    if tag != b"Xing" && tag != b"Info" {
+        return None;
+    }
+    Some((at, u32::from_be_bytes(frame.get(at + 4..at + 8)?.try_into().unwrap())))
+}
+
+/// Encoder delay and padding from the LAME extension that follows a
+/// Xing/Info header (at 0x9C in a stereo MPEG-1 frame with every Xing field
+/// present). Both are 12-bit sample counts. `None` without the `LAME`
+/// signature.
+pub fn lame_gapless(frame: &[u8]) -> Option<(u16, u16)> {
+    let (at, flags) = xing_header(frame)?;
+    // tag and flags, then the optional frames, bytes, TOC and quality fields
+    let lame_at = at + 8
+        + if flags & 0x01 != 0 { 4 } else { 0 }
+        + if flags & 0x02 != 0 { 4 } else { 0 }
+        + if flags & 0x04 != 0 { 100 } else { 0 }
+        + if flags & 0x08 != 0 { 4 } else { 0 };
+    if frame.get(lame_at..lame_at + 4)? != b"LAME" {
+        return None;
+    }
+    let b = frame.get(lame_at + 21..lame_at + 24)?;
+    let delay = (b[0] as u16) << 4 | (b[1] as u16) >> 4;
+    let padding = (b[1] as u16 & 0x0F) << 8 | b[2] as u16;
+    Some((delay, padding))
+}
+
+/// Frame count from a Fraunhofer VBRI header, which sits a fixed 32 bytes
+/// after the frame header regardless of MPEG version or channel mode.
+pub fn vbri_frame_count(frame: &[u8]) -> Option<u32> {
+    const AT: usize = 36;
+    if frame.get(AT..AT + 4)? != b"VBRI" {
+        return None;
+    }
+    // version, delay and quality (2 bytes each), stream bytes (4), frames (4)
+    Some(u32::from_be_bytes(frame.get(AT + 14..AT + 18)?.try_into().unwrap()))
+}
+
+/// Bitrate of uncompressed PCM: sample rate × bit depth × channels.
+pub fn pcm_bitrate_kbps(sample_rate: u32, bits: u16, channels: u16) -> Option<u32> {
+    let bps = sample_rate as u64 * bits as u64 * channels as u64;
+    u32::try_from(bps / 1000).ok().filter(|&kbps| kbps > 0)
+}
+
+/// Average bitrate in bits per second from an MP4 `esds` box body: the
+/// `DecoderConfigDescriptor` (tag 4) inside the `ES_Descriptor` (tag 3).
+pub fn esds_avg_bitrate(esds: &[u8]) -> Option<u32> {
+    // descriptor lengths use up to four 7-bit bytes
+    fn descriptor(data: &[u8], tag: u8) -> Option<&[u8]> {
+        if *data.first()? != tag {
+            return None;
+        }
+        let mut len = 0usize;
+        let mut i = 1;
+        loop {
+            let b = *data.get(i)?;
+            len = (len << 7) | (b & 0x7F) as usize;
+            i += 1;
+            if b & 0x80 == 0 || i == 5 {
+                break;
+            }
+        }
+        data.get(i..i + len).or_else(|| data.get(i..))
+    }
+
+    let es = descriptor(esds.get(4..)?, 3)?;
+    let flags = *es.get(2)?;
+    let mut at = 3;
+    if flags & 0x80 != 0 {
+        at += 2; // dependsOn_ES_ID
+    }
+    if flags & 0x40 != 0 {
+        at += 1 + *es.get(at)? as usize; // URL
+    }
+    if flags & 0x20 != 0 {
+        at += 2; // OCR_ES_ID
+    }
+    let config = descriptor(es.get(at..)?, 4)?;
+    // objectType (1), streamType (1), bufferSize (3), maxBitrate (4), avgBitrate (4)
+    Some(u32::from_be_bytes(config.get(9..13)?.try_into().unwrap()))
+}
+
+/// 64-bit FNV-1a, used to fingerprint cover images. Stable across runs and
+/// platforms, so hashes can be cached alongside a scan.
+pub fn fnv1a64(data: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &b in data {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    hash
+}
+
+/// CRC-16 as used by MPEG audio frames (polynomial 0x8005, initial 0xFFFF).
+pub fn crc16_mpeg(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in data {
+        for bit in (0..8).rev() {
+            let carry = ((crc >> 15) & 1) ^ ((byte as u16 >> bit) & 1);
+            crc <<= 1;
+            if carry != 0 {
+                crc ^= 0x8005;
+            }
+        }
+    }
+    crc
+}
+
+/// The whole contents of a source, for parsers that need random access over it.
+pub enum FileBytes<'a> {
+    Heap(Vec<u8>),
+    Borrowed(&'a [u8]),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Deref for FileBytes<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileBytes::Heap(data) => data,
+            FileBytes::Borrowed(data) => data,
+            #[cfg(feature = "mmap")]
+            FileBytes::Mapped(map) => map,
+        }
+    }
+}
+
+/// A seekable input the whole-file parsers (M4A atoms) can load
+/// in one go.
+pub trait Source: Read + Seek {
+    fn read_all(&mut self) -> io::Result<FileBytes<'_>>;
+}
+
+/// With the `mmap` feature the file is memory-mapped instead of read, which
+/// avoids a heap copy of multi-gigabyte files.
+impl Source for File {
+    fn read_all(&mut self) -> io::Result<FileBytes<'_>> {
+        #[cfg(feature = "mmap")]
+        if self.metadata()?.len() > 0 {
+            // SAFETY: the map is read-only and dropped before parsing returns.
+            // Another process truncating the file while it is mapped can still
+            // fault (SIGBUS on Unix); that is the documented caveat of `mmap`.
+            let map = unsafe { memmap2::Mmap::map(&*self)? };
+            return Ok(FileBytes::Mapped(map));
+        }
+
+        Ok(FileBytes::Heap(read_to_vec(self)?))
+    }
+}
+
+/// Any other seekable reader; the whole-file parsers read it into memory.
+pub struct Reader<'a, R>(pub &'a mut R);
+
+impl<R: Read> Read for Reader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<R: Seek> Seek for Reader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+impl<R: Read + Seek> Source for Reader<'_, R> {
+    fn read_all(&mut self) -> io::Result<FileBytes<'_>> {
+        Ok(FileBytes::Heap(read_to_vec(self)?))
+    }
+}
+
+/// In-memory input is handed over as is.
+impl Source for Cursor<&[u8]> {
+    fn read_all(&mut self) -> io::Result<FileBytes<'_>> {
+        Ok(FileBytes::Borrowed(self.get_ref()))
+    }
+}
+
+/// Reads encoder delay and padding from an iTunes `iTunSMPB` string:
+/// space-separated hex fields where the second is the delay (priming
+/// samples) and the third the padding.
+pub fn parse_itunsmpb(s: &str) -> Option<(u16, u16)> {
+    let mut fields = s.split_whitespace().skip(1);
+    let mut next = || u16::from_str_radix(fields.next()?, 16).ok();
+    Some((next()?, next()?))
+}
+
+/// A window sliding front to back over a reader, so a stream of any length
+/// can be scanned in bounded memory.
+pub struct SlidingWindow<R> {
+    reader: R,
+    buf: Vec<u8>,
+    /// Stream offset of `buf[0]`.
+    start: usize,
+    eof: bool,
+}
+
+impl<R: Read> SlidingWindow<R> {
+    const CHUNK: usize = 64 * 1024;
+
+    pub fn new(reader: R) -> Self {
+        Self { reader, buf: Vec::new(), start: 0, eof: false }
+    }
+
+    /// The bytes from stream offset `pos` on: at least `want` of them unless
+    /// the stream ends first. Offsets before an earlier `pos` may be gone.
+    pub fn at(&mut self, pos: usize, want: usize) -> io::Result<&[u8]> {
+        let pos = pos.max(self.start);
+        if pos - self.start >= Self::CHUNK || pos > self.start + self.buf.len() {
+            let consumed = (pos - self.start).min(self.buf.len());
+            self.buf.drain(..consumed);
+            self.start += consumed;
+        }
+        // a jump past the buffered bytes (an ID3v2 tag) is skipped unread
+        if pos > self.start && self.buf.is_empty() && !self.eof {
+            let gap = (pos - self.start) as u64;
+            let skipped = io::copy(&mut self.reader.by_ref().take(gap), &mut io::sink())?;
+            self.start += skipped as usize;
+            self.eof = skipped < gap;
+        }
+        while !self.eof && self.start + self.buf.len() < pos + want {
+            let len = self.buf.len();
+            self.buf.resize(len + Self::CHUNK, 0);
+            let read = self.reader.read(&mut self.buf[len..]);
+            self.buf.truncate(len + *read.as_ref().unwrap_or(&0));
+            match read {
+                Ok(read) => self.eof = read == 0,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(self.buf.get(pos - self.start..).unwrap_or(&[]))
+    }
+}
+
+/// Reads a whole seekable stream from the start.
+pub fn read_to_vec<R: Read + Seek>(r: &mut R) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    r.seek(SeekFrom::Start(0))?;
+    r.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Converts a sample count to milliseconds, clamped to `u64`. `None` for a
+/// zero sample rate.
+pub fn samples_to_ms(samples: u128, sample_rate: u32) -> Option<u64> {
+    if sample_rate == 0 {
+        return None;
+    }
+    Some(u64::try_from(samples * 1000 / sample_rate as u128).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_sample_rates() {
+        // 44100 = 0xAC44 * 2^0, normalised so the top mantissa bit is set
+        let rate_44k = [0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0];
+        assert_eq!(extended_to_f64(rate_44k), 44_100.0);
+        let rate_48k = [0x40, 0x0E, 0xBB, 0x80, 0, 0, 0, 0, 0, 0];
+        assert_eq!(extended_to_f64(rate_48k), 48_000.0);
+        assert_eq!(extended_to_f64([0; 10]), 0.0);
+    }
+
+    #[test]
+    fn synchsafe_round_trip() {
+        for n in [0, 1, 0x7F, 0x80, 0x3FFF, 0x4000, 257_000, 0x0FFF_FFFF] {
+            assert_eq!(synchsafe_to_u32(&synchsafe_from_u32(n)), n);
+        }
+        assert_eq!(synchsafe_from_u32(0x80), [0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn gain_strings() {
+        assert_eq!(parse_gain_db("-6.48 dB"), Some(-6.48));
+        assert_eq!(parse_gain_db("+1.5"), Some(1.5));
+        assert_eq!(parse_gain_db("loud"), None);
+    }
+
+    #[test]
+    fn itunnorm_gain() {
+        let norm = " 000003E8 000001F4 00003F8D 00003F8D 0002B1C5 0002B1C5 00007FFF 00007FFF 00002D4E 00002D4E";
+        assert_eq!(parse_itunnorm(norm), Some(0.0));
+        let gain = parse_itunnorm("00002710 00002710 0 0 0 0 0 0 0 0").unwrap();
+        assert!((gain + 10.0).abs() < 1e-4);
+        assert_eq!(parse_itunnorm("zz"), None);
+    }
+
+    #[test]
+    fn itunsmpb_delay_and_padding() {
+        let smpb = " 00000000 00000840 000001CA 00000000003F31F6 00000000 00000000";
+        assert_eq!(parse_itunsmpb(smpb), Some((2112, 458)));
+        assert_eq!(parse_itunsmpb(" 00000000"), None);
+    }
+}
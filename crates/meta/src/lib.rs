@@ -1,72 +1,459 @@
+mod album;
+#[cfg(feature = "serde")]
+mod base64;
+#[cfg(feature = "serde")]
+mod cache;
+mod error;
 mod helpers;
-
-use helpers::{trim_id3v1_text, synchsafe_to_u32, decode_text_frame, parse_vorbis_comments, extract_m4a_text };
-use std::fs::File;
-use std::io::{self, Read, Seek, SeekFrom};
+mod scan;
+
+pub use album::detect_album_gapless;
+#[cfg(feature = "serde")]
+pub use cache::{load_cache, load_cache_from, save_cache, save_cache_to, scan_with_cache, scan_with_cache_in, CacheEntry};
+pub use error::{MetaError, ParseWarning};
+pub use scan::scan_all;
+
+use helpers::{clean_tag_text, id3v1_genre_name, id3v2_genres, split_text_values, trim_id3v1_text, synchsafe_to_u32, decode_text_frame, parse_vorbis_comments, extract_m4a_text, extract_m4a_freeform, parse_gain_db, parse_itunnorm,
+    id3v2_frame_size, id3v2_frame_body, v23_to_v24_frame_id, remove_unsynchronisation, id3v2_extended_header_len, m4a_atoms, m4a_atom_headers, find_m4a_atom, find_m4a_audio_mdhd, mp3_free_format_len, mp3_frame_len, mp3_frames_follow, SlidingWindow, Picture, parse_apic, parse_flac_picture, parse_m4a_cover_data,
+    preferred_picture, fnv1a64, PICTURE_HEADER_PEEK, crc16_mpeg, mpeg_side_info_len, xing_frame_count, pcm_bitrate_kbps, esds_avg_bitrate, vbri_frame_count, Source, Reader,
+    id3v2_frames_with_flags, id3v2_frames_recovering, id3v24_frame, id3v24_text_frame, synchsafe_from_u32, parse_described_text, parse_flag, parse_popm, extract_m4a_integer, parse_ufid, musicbrainz_id, lame_gapless,
+    extract_m4a_number_pair, extended_to_f64, ogg_page, ogg_packets, ogg_last_granule, OGG_MAX_PAGE, IFF_MAX_CHUNKS, parse_number_pair, parse_year, parse_bext, parse_itunsmpb, samples_to_ms, read_to_vec };
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::time::Duration;
+
+/// The container detected by [`SongMetadata::from_file`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AudioFormat {
+    Mp3,
+    Flac,
+    Wav,
+    M4a,
+    Aiff,
+    /// Ogg Vorbis
+    Vorbis,
+    /// Ogg Opus
+    Opus,
+    #[default]
+    Unknown,
+}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
 pub struct SongMetadata {
+    /// Container the metadata was read from.
+    pub format: AudioFormat,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub artist: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub title: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub album: Option<String>,
+    /// Artist credited for the whole album (e.g. "Various Artists"). Never
+    /// copied into `artist`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub album_artist: Option<String>,
+    /// Composer (ID3v2 `TCOM`, Vorbis `COMPOSER`, MP4 `©wrt`), kept apart
+    /// from the performing artist.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub composer: Option<String>,
+    /// Sort-order overrides (e.g. `Beatles, The`) from ID3v2 `TSOT`/`TSOP`/
+    /// `TSOA`, Vorbis `TITLESORT`/`ARTISTSORT`/`ALBUMSORT` or MP4 `sonm`/
+    /// `soar`/`soal`. `None` when untagged; callers fall back to the display
+    /// fields themselves.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub title_sort: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub artist_sort: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub album_sort: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub genre: Option<String>,
+    /// Whether the track belongs to a compilation (iTunes "part of a
+    /// compilation": MP4 `cpil`, ID3v2 `TCMP`, Vorbis `COMPILATION`), so it
+    /// groups under one album rather than per artist.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub compilation: Option<bool>,
+    /// Free-text comment or annotation (ID3v2 `COMM`, ID3v1, Vorbis
+    /// `COMMENT`/`DESCRIPTION`, MP4 `©cmt`, WAV `ICMT`, AIFF `ANNO`).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub comment: Option<String>,
+    /// Rating on the raw ID3v2 `POPM` scale, 0–255 (0 = unrated). The usual
+    /// star mapping is 1 = 1 star, 64 = 2, 128 = 3, 196 = 4 and 255 = 5.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub rating: Option<u8>,
+    /// Unsynchronised lyrics (ID3v2 `USLT`, Vorbis `LYRICS`, MP4 `©lyr`).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub lyrics: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub copyright: Option<String>,
+    /// Every artist from a multi-value ID3v2 `TPE1` frame; `artist` holds the
+    /// first of them.
+    pub artists: Vec<String>,
+    /// Every genre from a multi-value ID3v2 `TCON` frame; `genre` holds the
+    /// first of them.
+    pub genres: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub duration_ms: Option<u64>, // ← NEW
+    /// Audio bitrate in kbps: the frame bitrate for CBR MP3, an average for
+    /// VBR MP3, the uncompressed PCM rate for FLAC/WAV, and the `esds`
+    /// average for M4A.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub bitrate_kbps: Option<u32>,
+    /// Sample rate in Hz.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub sample_rate: Option<u32>,
+    /// Number of audio channels (1 = mono, 2 = stereo).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub channels: Option<u8>,
+    /// ReplayGain track gain in dB (iTunes Sound Check is used as a fallback).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub replay_gain_track_db: Option<f32>,
+    /// ReplayGain album gain in dB.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub replay_gain_album_db: Option<f32>,
+    /// Whether the MP3 frames carry a CRC-16 (protection bit clear).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub crc_protected: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub track_number: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub track_total: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub disc_number: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub disc_total: Option<u32>,
+    /// Release year, taken from the leading digits of a full date.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub year: Option<i32>,
+    /// Broadcast WAV production metadata from the `bext` chunk.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub bext: Option<BextMetadata>,
+    /// Encoder or encoding tool that produced the file (ID3v2 `TSSE`, Vorbis
+    /// `ENCODER`/`ENCODED_BY`, MP4 `©too`), e.g. `LAME 3.100`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub encoder: Option<String>,
+    /// Encoder delay in samples (priming), for gapless playback.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub encoder_delay: Option<u16>,
+    /// Encoder padding in samples at the end of the stream.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub encoder_padding: Option<u16>,
+    /// MusicBrainz track (recording) ID, usually a UUID.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub musicbrainz_track_id: Option<String>,
+    /// MusicBrainz album (release) ID.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub musicbrainz_album_id: Option<String>,
+    /// The front cover (else the first picture), only filled in when parsing
+    /// with [`ParseOptions::cover`] set, e.g. via [`SongMetadata::reader`].
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub cover: Option<CoverArt>,
+    /// Every M4A freeform (`----`) item by its `name`, e.g.
+    /// `MusicBrainz Track Id`, including those also read into fields above.
+    pub extra: HashMap<String, String>,
+}
+
+/// Fields of a Broadcast Wave Format `bext` chunk.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct BextMetadata {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub description: Option<String>,
+    /// Name of the device or organisation that produced the recording.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub originator: Option<String>,
+    /// `yyyy-mm-dd`
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub origination_date: Option<String>,
+    /// `hh:mm:ss`
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub origination_time: Option<String>,
+    /// Timecode of the first sample, counted in samples since midnight.
+    pub time_reference: u64,
+}
+
+/// An embedded picture (ID3v2 `APIC`, FLAC `PICTURE` or MP4 `covr` image).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoverArt {
+    pub mime: String,
+    /// ID3v2/FLAC picture type: 3 = front cover, 4 = back cover, 0 = other.
+    pub kind: u8,
+    pub description: String,
+    /// Width and height in pixels, when recorded (FLAC `PICTURE` blocks).
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub dimensions: Option<(u32, u32)>,
+    /// Base64-encoded when serialized.
+    #[cfg_attr(feature = "serde", serde(with = "base64"))]
+    pub data: Vec<u8>,
+}
+
+impl From<Picture> for CoverArt {
+    fn from(p: Picture) -> Self {
+        CoverArt {
+            mime: p.mime,
+            kind: p.kind,
+            description: p.description,
+            dimensions: p.dimensions,
+            data: p.data,
+        }
+    }
+}
+
+/// What to parse, and limits applied while parsing so untrusted files can't
+/// exhaust memory. The defaults match [`SongMetadata::from_file`].
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Embedded pictures larger than this are not loaded; their MIME type and
+    /// declared size are still recorded. Defaults to 32 MiB.
+    pub max_cover_bytes: usize,
+    /// Read tag fields (title, artist, ...). When off, tag chunks, blocks and
+    /// atoms are skipped unread and only the format and stream properties are
+    /// returned. Defaults to `true`.
+    pub tags: bool,
+    /// Compute the duration. This is what scans every MP3 frame, so turning
+    /// it off also leaves the MP3 bitrate, sample rate and channels unset.
+    /// Defaults to `true`.
+    pub duration: bool,
+    /// Load the front cover into [`SongMetadata::cover`]. Defaults to `false`.
+    pub cover: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_cover_bytes: 32 * 1024 * 1024,
+            tags: true,
+            duration: true,
+            cover: false,
+        }
+    }
+}
+
+/// Builder for parsing with [`ParseOptions`], from [`SongMetadata::reader`]:
+/// `SongMetadata::reader().duration(false).from_file(path)` for a quick
+/// title/artist listing.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataReader {
+    options: ParseOptions,
+}
+
+impl MetadataReader {
+    pub fn tags(mut self, enabled: bool) -> Self {
+        self.options.tags = enabled;
+        self
+    }
+
+    pub fn duration(mut self, enabled: bool) -> Self {
+        self.options.duration = enabled;
+        self
+    }
+
+    pub fn cover(mut self, enabled: bool) -> Self {
+        self.options.cover = enabled;
+        self
+    }
+
+    pub fn max_cover_bytes(mut self, max: usize) -> Self {
+        self.options.max_cover_bytes = max;
+        self
+    }
+
+    /// Like [`SongMetadata::from_file`], including the filename fallback for
+    /// `title` when tags are read.
+    pub fn from_file<P: AsRef<Path>>(&self, path: P) -> Result<SongMetadata, MetaError> {
+        let path_ref = path.as_ref();
+        let mut meta = SongMetadata::parse_with(&mut File::open(path_ref)?, &self.options, None)?;
+        if self.options.tags && meta.title.is_none() {
+            meta.title = Some(SongMetadata::prettify_filename(path_ref));
+        }
+        Ok(meta)
+    }
+
+    /// Like [`SongMetadata::from_bytes`].
+    pub fn from_bytes(&self, data: &[u8]) -> Result<SongMetadata, MetaError> {
+        SongMetadata::parse_with(&mut Cursor::new(data), &self.options, None)
+    }
 }
 
 impl SongMetadata {
-    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+    /// Starts a [`MetadataReader`] for choosing what gets parsed.
+    pub fn reader() -> MetadataReader {
+        MetadataReader::default()
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, MetaError> {
         let path_ref = path.as_ref();
-        let mut f = File::open(path_ref)?;
+        let mut meta = Self::parse(&mut File::open(path_ref)?)?;
+
+        // ✅ Automatically assign filename as title if missing
+        if meta.title.is_none() {
+            meta.title = Some(Self::prettify_filename(path_ref));
+        }
+
+        Ok(meta)
+    }
+
+    /// Like [`SongMetadata::from_file`], but a damaged ID3v2 frame doesn't
+    /// end the tag: it is skipped, reported as a [`ParseWarning`], and the
+    /// frames after it are still read. Useful for importers that want to flag
+    /// damaged files while keeping whatever metadata survived.
+    pub fn from_file_verbose<P: AsRef<Path>>(path: P) -> Result<(Self, Vec<ParseWarning>), MetaError> {
+        let path_ref = path.as_ref();
+        let mut warnings = Vec::new();
+        let mut meta = Self::parse_with(&mut File::open(path_ref)?, &ParseOptions::default(), Some(&mut warnings))?;
+        if meta.title.is_none() {
+            meta.title = Some(Self::prettify_filename(path_ref));
+        }
+        Ok((meta, warnings))
+    }
+
+    /// Parses a file that is already in memory, e.g. an upload. Runs the same
+    /// format detection as [`SongMetadata::from_file`], but with no path there
+    /// is no filename to fall back on, so `title` stays `None` when untagged.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MetaError> {
+        Self::parse(&mut Cursor::new(data))
+    }
+
+    /// Parses any seekable reader: a `BufReader`, a memory map wrapped in a
+    /// `Cursor`, a network-backed stream and so on. Like
+    /// [`SongMetadata::from_bytes`] there is no filename fallback for `title`.
+    pub fn from_reader<R: Read + Seek>(r: &mut R) -> Result<Self, MetaError> {
+        Self::parse(&mut Reader(r))
+    }
+
+    fn parse<S: Source>(f: &mut S) -> Result<Self, MetaError> {
+        Self::parse_with(f, &ParseOptions::default(), None)
+    }
+
+    fn parse_with<S: Source>(f: &mut S, options: &ParseOptions, warnings: Option<&mut Vec<ParseWarning>>) -> Result<Self, MetaError> {
         let mut header = [0u8; 12];
         if f.read(&mut header)? < 12 {
-            return Ok(Self::default_with_filename(path_ref));
+            return Ok(Self::default());
         }
         f.seek(SeekFrom::Start(0))?;
 
         let mut meta = match &header[0..4] {
             b"RIFF" if &header[8..12] == b"WAVE" => {
-                let mut m = Self::from_wav(&mut f)?;
-                m.duration_ms = Self::wav_duration(&mut f).ok();
+                let mut m = Self::from_wav(f, options.tags)?;
+                if options.duration {
+                    m.duration_ms = Self::compute_wav_duration(f).ok();
+                }
+                m.format = AudioFormat::Wav;
                 m
             }
             b"fLaC" => {
-                let mut m = Self::from_flac(&mut f)?;
-                m.duration_ms = Self::flac_duration(&mut f).ok();
+                let mut m = Self::from_flac(f, options.tags)?;
+                if options.duration {
+                    m.duration_ms = Self::compute_flac_duration(f).ok();
+                }
+                m.format = AudioFormat::Flac;
+                m
+            }
+            b"FORM" if &header[8..12] == b"AIFF" || &header[8..12] == b"AIFC" => {
+                let mut m = if options.tags { Self::from_aiff(f)? } else { Self::default() };
+                if options.duration {
+                    m.duration_ms = Self::compute_aiff_duration(f).ok();
+                }
+                m.format = AudioFormat::Aiff;
                 m
             }
-            b"ID3\x03" | b"ID3\x04" => {
-                let mut m = Self::from_mp3v2(&mut f)?;
-                m.duration_ms = Self::mp3_duration(&mut f).ok();
+            b"OggS" => {
+                let mut m = Self::from_ogg(f, options.tags)?;
+                if options.duration {
+                    m.duration_ms = Self::compute_ogg_duration(f).ok();
+                }
+                m
+            }
+            b"ID3\x02" | b"ID3\x03" | b"ID3\x04" => {
+                let mut m = if options.tags { Self::from_mp3v2(f, warnings)? } else { Self::default() };
+                if options.duration {
+                    m.apply_mp3_scan(f);
+                }
+                m.format = AudioFormat::Mp3;
                 m
             }
             _ => {
-                // Try MP3v1, M4A, ID3v1 etc.
-                let mut m = if let Ok(m1) = Self::from_id3v1(&mut f) {
-                    m1
-                } else if let Ok(m1) = Self::from_m4a(&mut f) {
-                    m1
+                let mut m = if options.tags {
+                    Self::from_trailing_tags(f, warnings)?
+                } else if &header[4..8] == b"ftyp" {
+                    // no tags, but the sample entry still describes the stream
+                    Self::from_m4a(f, false).unwrap_or_default()
                 } else {
-                    SongMetadata::default()
+                    Self::default()
                 };
+                // MP4 files open with an `ftyp` atom
+                if &header[4..8] == b"ftyp" {
+                    m.format = AudioFormat::M4a;
+                }
                 // attempt M4A duration (if it was m4a) or MP3 duration as fallback
-                m.duration_ms = Self::m4a_duration(&mut f).ok().or_else(|| Self::mp3_duration(&mut f).ok());
+                if options.duration {
+                    m.duration_ms = Self::m4a_duration(f).ok();
+                    if m.duration_ms.is_none() {
+                        m.apply_mp3_scan(f);
+                        if m.format == AudioFormat::Unknown && m.sample_rate.is_some() {
+                            m.format = AudioFormat::Mp3;
+                        }
+                    }
+                }
                 m
             }
         };
-
-        // ✅ Automatically assign filename as title if missing
-        if meta.title.is_none() {
-            meta.title = Some(Self::prettify_filename(path_ref));
+        if !options.tags {
+            meta = meta.stream_properties();
+        }
+        if options.cover {
+            f.seek(SeekFrom::Start(0))?;
+            let mut pictures = Self::read_covers(f, options.max_cover_bytes)?;
+            pictures.retain(|p| !p.is_skipped());
+            meta.cover = preferred_picture(pictures).map(CoverArt::from);
         }
-
         Ok(meta)
     }
 
-    fn default_with_filename(path: &Path) -> Self {
-        let mut m = Self::default();
-        m.title = Some(Self::prettify_filename(path));
-        m
+    /// Tags of a file that doesn't open with one: an appended ID3v2 tag, an
+    /// ID3v1 tag, or else M4A atoms.
+    fn from_trailing_tags<S: Source>(f: &mut S, warnings: Option<&mut Vec<ParseWarning>>) -> Result<Self, MetaError> {
+        // an ID3v2.4 tag may be appended instead of leading
+        let appended = match Self::appended_id3v2_start(f)? {
+            Some(start) => {
+                f.seek(SeekFrom::Start(start))?;
+                Some(Self::from_mp3v2(f, warnings)?)
+            }
+            None => None,
+        };
+        // Try MP3v1, M4A, ID3v1 etc.
+        let mut m = if let Ok(mut m1) = Self::from_id3v1(f) {
+            m1.format = AudioFormat::Mp3;
+            m1
+        } else if appended.is_some() {
+            SongMetadata { format: AudioFormat::Mp3, ..SongMetadata::default() }
+        } else {
+            Self::from_m4a(f, true).unwrap_or_default()
+        };
+        if let Some(tag) = appended {
+            m.prefer_tag(tag);
+        }
+        Ok(m)
+    }
+
+    /// Only the fields describing the audio stream rather than its tags.
+    fn stream_properties(self) -> Self {
+        SongMetadata {
+            format: self.format,
+            duration_ms: self.duration_ms,
+            bitrate_kbps: self.bitrate_kbps,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            crc_protected: self.crc_protected,
+            encoder_delay: self.encoder_delay,
+            encoder_padding: self.encoder_padding,
+            ..SongMetadata::default()
+        }
     }
 
     /// Converts `foo_bar-baz.mp3` → `Foo Bar Baz`
@@ -76,8 +463,7 @@ impl SongMetadata {
             .unwrap_or("Unknown");
 
         file_name
-            .replace('_', " ")
-            .replace('-', " ")
+            .replace(['_', '-'], " ")
             .split_whitespace()
             .map(|w| {
                 let mut chars = w.chars();
@@ -90,129 +476,368 @@ impl SongMetadata {
             .join(" ")
     }
 
-    // --- WAV (LIST/INFO) parsing ---
-    fn from_wav(f: &mut File) -> io::Result<Self> {
+    // --- WAV (LIST/INFO, embedded ID3v2) parsing ---
+    fn from_wav<R: Read + Seek>(f: &mut R, tags: bool) -> Result<Self, MetaError> {
         let mut meta = SongMetadata::default();
+        let mut id3 = None;
         f.seek(SeekFrom::Start(12))?;
 
+        // every chunk ends with a seek to `next`, which is always past its
+        // 8-byte header, so each iteration moves forward
         let mut buf = [0u8; 8];
-        while f.read(&mut buf)? == 8 {
+        let mut chunks = 0;
+        while chunks < IFF_MAX_CHUNKS && f.read(&mut buf)? == 8 {
+            chunks += 1;
             let chunk_id = &buf[0..4];
             let chunk_size = u32::from_le_bytes(buf[4..8].try_into().unwrap()) as u64;
-            let next = f.seek(SeekFrom::Current(0))? + chunk_size;
-
-            if chunk_id == b"LIST" {
-                // Read list type (INFO or others)
-                let mut list_type = [0u8; 4];
-                f.read_exact(&mut list_type)?;
-                if &list_type == b"INFO" {
-                    let mut remaining = chunk_size - 4;
-                    while remaining >= 8 {
-                        let mut sub_header = [0u8; 8];
-                        if f.read(&mut sub_header)? != 8 {
-                            break;
-                        }
-                        let sub_id = &sub_header[0..4];
-                        let sub_size =
-                            u32::from_le_bytes(sub_header[4..8].try_into().unwrap()) as usize;
-
-                        let mut data = vec![0u8; sub_size];
-                        f.read_exact(&mut data)?;
-                        let text = String::from_utf8_lossy(&data)
-                            .trim_matches(char::from(0))
-                            .trim()
-                            .to_string();
-
-                        match sub_id {
-                            b"IART" => meta.artist = Some(text),
-                            b"INAM" => meta.title = Some(text),
-                            b"IPRD" => meta.album = Some(text),
-                            b"IGNR" => meta.genre = Some(text),
-                            _ => {}
-                        }
+            // chunks are padded to an even length
+            let next = f.stream_position()? + chunk_size + (chunk_size & 1);
 
-                        remaining = remaining.saturating_sub((8 + sub_size) as u64);
-                    }
-                } else {
-                    f.seek(SeekFrom::Start(next))?;
+            if !tags && chunk_id != b"fmt " {
+                f.seek(SeekFrom::Start(next))?;
+            } else if chunk_id == b"LIST" {
+                let mut list = Vec::new();
+                f.by_ref().take(chunk_size).read_to_end(&mut list)?;
+                // a LIST too short to hold its type ends right here
+                if list.len() >= 4 && &list[0..4] == b"INFO" {
+                    meta.apply_wav_info(&list[4..]);
                 }
+                f.seek(SeekFrom::Start(next))?;
+            } else if chunk_id == b"fmt " {
+                let mut fmt = Vec::new();
+                f.by_ref().take(chunk_size).read_to_end(&mut fmt)?;
+                if fmt.len() >= 16 {
+                    let channels = u16::from_le_bytes([fmt[2], fmt[3]]);
+                    let sample_rate = u32::from_le_bytes(fmt[4..8].try_into().unwrap());
+                    let bits = u16::from_le_bytes([fmt[14], fmt[15]]);
+                    meta.bitrate_kbps = pcm_bitrate_kbps(sample_rate, bits, channels);
+                    meta.sample_rate = Some(sample_rate).filter(|&r| r > 0);
+                    meta.channels = u8::try_from(channels).ok().filter(|&c| c > 0);
+                }
+                f.seek(SeekFrom::Start(next))?;
+            } else if chunk_id == b"bext" {
+                let mut data = Vec::new();
+                f.by_ref().take(chunk_size).read_to_end(&mut data)?;
+                meta.bext = parse_bext(&data);
+                f.seek(SeekFrom::Start(next))?;
+            } else if chunk_id == b"id3 " || chunk_id == b"ID3 " {
+                let mut data = Vec::new();
+                f.by_ref().take(chunk_size).read_to_end(&mut data)?;
+                if let Ok((major, flags, tag_data)) = Self::read_id3v2_tag(&mut Cursor::new(data.as_slice())) {
+                    id3 = Some(Self::from_id3v2_frames(&tag_data, major, flags, None));
+                }
+                f.seek(SeekFrom::Start(next))?;
             } else {
                 f.seek(SeekFrom::Start(next))?;
             }
         }
+        // an embedded ID3v2 tag wins over INFO, which may come before or after it
+        if let Some(tag) = id3 {
+            meta.prefer_tag(tag);
+        }
+        Ok(meta)
+    }
+
+    /// Applies the sub-chunks of a `LIST/INFO` body. A sub-chunk claiming
+    /// more bytes than the list holds ends it.
+    fn apply_wav_info(&mut self, info: &[u8]) {
+        let mut pos = 0;
+        while let Some(sub_header) = info.get(pos..pos + 8) {
+            let sub_id = &sub_header[0..4];
+            let sub_size = u32::from_le_bytes(sub_header[4..8].try_into().unwrap()) as usize;
+            let Some(data) = info.get(pos + 8..pos + 8 + sub_size) else {
+                break;
+            };
+            let text = clean_tag_text(&String::from_utf8_lossy(data));
+
+            match sub_id {
+                b"IART" => self.artist = text,
+                b"INAM" => self.title = text,
+                b"IPRD" => self.album = text,
+                b"IGNR" => self.genre = text,
+                b"ICMT" => self.comment = text,
+                b"ICRD" => self.year = text.and_then(|t| parse_year(&t)),
+                b"ITRK" => {
+                    (self.track_number, self.track_total) =
+                        text.map_or((None, None), |t| parse_number_pair(&t));
+                }
+                _ => {}
+            }
+
+            pos += 8 + sub_size + (sub_size & 1);
+        }
+    }
+
+    // --- AIFF (big-endian IFF chunks) ---
+    /// Reads the `NAME`, `AUTH`, `ANNO` and `(c) ` text chunks; the duration
+    /// comes from [`SongMetadata::compute_aiff_duration`]. Chunks are padded
+    /// to an even length.
+    fn from_aiff<R: Read + Seek>(f: &mut R) -> Result<Self, MetaError> {
+        let mut meta = SongMetadata::default();
+        f.seek(SeekFrom::Start(12))?;
+
+        let mut buf = [0u8; 8];
+        let mut chunks = 0;
+        while chunks < IFF_MAX_CHUNKS && f.read(&mut buf)? == 8 {
+            chunks += 1;
+            let chunk_id = &buf[0..4];
+            let chunk_size = u32::from_be_bytes(buf[4..8].try_into().unwrap()) as u64;
+            let next = f.stream_position()? + chunk_size + (chunk_size & 1);
+
+            // only small text chunks are loaded, never the SSND audio
+            if !matches!(chunk_id, b"NAME" | b"AUTH" | b"ANNO" | b"(c) ") {
+                f.seek(SeekFrom::Start(next))?;
+                continue;
+            }
+            let mut data = Vec::new();
+            f.by_ref().take(chunk_size).read_to_end(&mut data)?;
+            let text = || clean_tag_text(&String::from_utf8_lossy(&data));
+            match chunk_id {
+                b"NAME" => meta.title = text(),
+                b"AUTH" => meta.artist = text(),
+                b"ANNO" => meta.comment = text(),
+                b"(c) " => meta.copyright = text(),
+                _ => {}
+            }
+            f.seek(SeekFrom::Start(next))?;
+        }
         Ok(meta)
     }
 
     // --- MP3v1 ---
-    fn from_id3v1(f: &mut File) -> io::Result<Self> {
+    fn from_id3v1<R: Read + Seek>(f: &mut R) -> Result<Self, MetaError> {
         let len = f.seek(SeekFrom::End(0))?;
         if len < 128 {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no id3v1"));
+            return Err(MetaError::Truncated { expected: 128, got: len as usize });
         }
         f.seek(SeekFrom::End(-128))?;
         let mut buf = [0u8; 128];
         f.read_exact(&mut buf)?;
         if &buf[0..3] != b"TAG" {
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "no TAG header"));
+            return Err(MetaError::InvalidTag("ID3v1 tag"));
         }
 
-        let title = trim_id3v1_text(&buf[3..33]);
-        let artist = trim_id3v1_text(&buf[33..63]);
-        let album = trim_id3v1_text(&buf[63..93]);
-        let genre = Some(format!("{}", buf[127]));
+        let mut title = trim_id3v1_text(&buf[3..33]);
+        let mut artist = trim_id3v1_text(&buf[33..63]);
+        let mut album = trim_id3v1_text(&buf[63..93]);
+        let mut genre = id3v1_genre_name(buf[127]).map(str::to_string);
+        let year = trim_id3v1_text(&buf[93..97]).and_then(|y| parse_year(&y));
+        // ID3v1.1: a zero byte ends a shortened comment, then the track number
+        let track_number = (buf[125] == 0 && buf[126] != 0).then_some(buf[126] as u32);
+        let comment = trim_id3v1_text(if track_number.is_some() { &buf[97..125] } else { &buf[97..127] });
+
+        // Enhanced tag: 227 bytes just before the ID3v1 tag, marked `TAG+`.
+        // Its title/artist/album hold the 60 characters that follow the 30
+        // in the ID3v1 fields; its genre is free text.
+        if len >= 128 + 227 {
+            let mut ext = [0u8; 227];
+            f.seek(SeekFrom::End(-(128 + 227)))?;
+            f.read_exact(&mut ext)?;
+            if &ext[0..4] == b"TAG+" {
+                let joined = |v1: &[u8], more: &[u8]| trim_id3v1_text(&[v1, more].concat());
+                title = joined(&buf[3..33], &ext[4..64]).or(title);
+                artist = joined(&buf[33..63], &ext[64..124]).or(artist);
+                album = joined(&buf[63..93], &ext[124..184]).or(album);
+                genre = trim_id3v1_text(&ext[185..215]).or(genre);
+            }
+        }
 
         Ok(SongMetadata {
             artist,
             title,
             album,
             genre,
-            duration_ms: None,
+            comment,
+            track_number,
+            year,
+            ..SongMetadata::default()
         })
     }
 
     // --- MP3v2 ---
-    fn from_mp3v2(f: &mut File) -> io::Result<Self> {
-        let mut header = [0u8; 10];
-        f.read_exact(&mut header)?;
-        if &header[0..3] != b"ID3" {
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "no id3v2 header"));
-        }
-
-        let tag_size = synchsafe_to_u32(&header[6..10]) as usize;
-        let mut tag_data = vec![0u8; tag_size];
-        f.read_exact(&mut tag_data)?;
+    fn from_mp3v2<R: Read + Seek>(f: &mut R, warnings: Option<&mut Vec<ParseWarning>>) -> Result<Self, MetaError> {
+        let (major, flags, tag_data) = Self::read_id3v2_tag(f)?;
+        Ok(Self::from_id3v2_frames(&tag_data, major, flags, warnings))
+    }
 
+    /// Reads the frames of an ID3v2 tag body, as returned by
+    /// `read_id3v2_tag`. Shared by MP3 files and the WAV `id3 ` chunk. With
+    /// `warnings`, damaged frames are skipped and recorded rather than ending
+    /// the walk.
+    fn from_id3v2_frames(tag_data: &[u8], major: u8, flags: u8, warnings: Option<&mut Vec<ParseWarning>>) -> Self {
         let mut meta = SongMetadata::default();
-        let mut i = 0;
-        while i + 10 <= tag_data.len() {
-            let id = &tag_data[i..i + 4];
-            let size = u32::from_be_bytes(tag_data[i + 4..i + 8].try_into().unwrap()) as usize;
-            if size == 0 || i + 10 + size > tag_data.len() {
-                break;
-            }
-            let frame = &tag_data[i + 10..i + 10 + size];
-            let text = decode_text_frame(frame);
+        for (id, [_, format], frame) in id3v2_frames_recovering(tag_data, major, warnings) {
+            let Some(frame) = id3v2_frame_body(frame, major, format, flags) else {
+                continue;
+            };
+            let text = decode_text_frame(&frame);
 
             match id {
                 b"TIT2" => meta.title = text,
-                b"TPE1" => meta.artist = text,
+                b"TPE1" => {
+                    meta.artists = split_text_values(text);
+                    meta.artist = meta.artists.first().cloned();
+                }
                 b"TALB" => meta.album = text,
-                b"TCON" => meta.genre = text,
+                b"TPE2" => meta.album_artist = text,
+                b"TCOM" => meta.composer = text,
+                b"TSOT" => meta.title_sort = text,
+                b"TSOP" => meta.artist_sort = text,
+                b"TSOA" => meta.album_sort = text,
+                b"TSSE" => meta.encoder = text,
+                b"TCMP" => meta.compilation = text.and_then(|t| parse_flag(&t)),
+                b"TCON" => {
+                    meta.genres = split_text_values(text).into_iter().flat_map(id3v2_genres).collect();
+                    meta.genre = meta.genres.first().cloned();
+                }
+                // TYER in ID3v2.3, TDRC (a timestamp) in ID3v2.4
+                b"TYER" | b"TDRC" => meta.year = text.and_then(|t| parse_year(&t)),
+                b"TRCK" => {
+                    (meta.track_number, meta.track_total) =
+                        text.map_or((None, None), |t| parse_number_pair(&t));
+                }
+                b"TPOS" => {
+                    (meta.disc_number, meta.disc_total) =
+                        text.map_or((None, None), |t| parse_number_pair(&t));
+                }
+                b"USLT" => meta.lyrics = parse_described_text(&frame).map(|(_, lyrics)| lyrics),
+                // iTunes keeps machine data (iTunNORM, iTunSMPB, ...) in COMM
+                // frames of its own; the first other one is the comment
+                b"COMM" if meta.comment.is_none() => {
+                    meta.comment = parse_described_text(&frame)
+                        .filter(|(description, _)| !description.starts_with("iTun"))
+                        .map(|(_, comment)| comment);
+                }
+                b"POPM" => meta.rating = parse_popm(&frame),
+                b"UFID" => {
+                    if let Some(id) = parse_ufid(&frame, "http://musicbrainz.org") {
+                        meta.musicbrainz_track_id = Some(id);
+                    }
+                }
+                // user-defined text: a description, a null, then the value
+                b"TXXX" => {
+                    if let Some((key, value)) = text.as_deref().and_then(|t| t.split_once('\0')) {
+                        match key.to_ascii_lowercase().as_str() {
+                            "replaygain_track_gain" => meta.replay_gain_track_db = parse_gain_db(value),
+                            "replaygain_album_gain" => meta.replay_gain_album_db = parse_gain_db(value),
+                            // `UFID` holds the same ID and takes precedence
+                            "musicbrainz track id" if meta.musicbrainz_track_id.is_none() => {
+                                meta.musicbrainz_track_id = musicbrainz_id(value);
+                            }
+                            "musicbrainz album id" => meta.musicbrainz_album_id = musicbrainz_id(value),
+                            _ => {}
+                        }
+                    }
+                }
                 _ => {}
             }
+        }
+
+        meta
+    }
+
+    /// Finds an ID3v2.4 tag appended to the end of the file by its `3DI`
+    /// footer, either last in the file or just before an ID3v1 tag, and
+    /// returns the offset of its header.
+    fn appended_id3v2_start<R: Read + Seek>(f: &mut R) -> Result<Option<u64>, MetaError> {
+        let len = f.seek(SeekFrom::End(0))?;
+        for end in [len, len.saturating_sub(128)] {
+            if end < 20 {
+                continue;
+            }
+            let mut footer = [0u8; 10];
+            f.seek(SeekFrom::Start(end - 10))?;
+            f.read_exact(&mut footer)?;
+            if &footer[0..3] != b"3DI" {
+                continue;
+            }
+            // header + frames + footer
+            let Some(start) = end.checked_sub(20 + synchsafe_to_u32(&footer[6..10]) as u64) else {
+                continue;
+            };
+            let mut magic = [0u8; 3];
+            f.seek(SeekFrom::Start(start))?;
+            f.read_exact(&mut magic)?;
+            if &magic == b"ID3" {
+                return Ok(Some(start));
+            }
+        }
+        Ok(None)
+    }
 
-            i += 10 + size;
+    /// Takes every field an ID3v2 tag provides from `tag`, keeping the
+    /// current value (e.g. from a WAV INFO chunk) only where the tag has none.
+    fn prefer_tag(&mut self, tag: SongMetadata) {
+        self.title = tag.title.or(self.title.take());
+        self.album = tag.album.or(self.album.take());
+        self.album_artist = tag.album_artist.or(self.album_artist.take());
+        self.composer = tag.composer.or(self.composer.take());
+        self.title_sort = tag.title_sort.or(self.title_sort.take());
+        self.artist_sort = tag.artist_sort.or(self.artist_sort.take());
+        self.album_sort = tag.album_sort.or(self.album_sort.take());
+        if !tag.artists.is_empty() {
+            (self.artist, self.artists) = (tag.artist, tag.artists);
+        }
+        if !tag.genres.is_empty() {
+            (self.genre, self.genres) = (tag.genre, tag.genres);
         }
+        self.year = tag.year.or(self.year);
+        if tag.track_number.is_some() {
+            (self.track_number, self.track_total) = (tag.track_number, tag.track_total);
+        }
+        if tag.disc_number.is_some() {
+            (self.disc_number, self.disc_total) = (tag.disc_number, tag.disc_total);
+        }
+        self.lyrics = tag.lyrics.or(self.lyrics.take());
+        self.comment = tag.comment.or(self.comment.take());
+        self.encoder = tag.encoder.or(self.encoder.take());
+        self.compilation = tag.compilation.or(self.compilation);
+        self.rating = tag.rating.or(self.rating);
+        self.replay_gain_track_db = tag.replay_gain_track_db.or(self.replay_gain_track_db);
+        self.replay_gain_album_db = tag.replay_gain_album_db.or(self.replay_gain_album_db);
+        self.musicbrainz_track_id = tag.musicbrainz_track_id.or(self.musicbrainz_track_id.take());
+        self.musicbrainz_album_id = tag.musicbrainz_album_id.or(self.musicbrainz_album_id.take());
+    }
 
-        Ok(meta)
+    /// Reads the ID3v2 header at the current position and returns the major
+    /// version, the header flags and the tag body. A v2.2/v2.3 tag with the
+    /// unsynchronisation flag is decoded here, before frame sizes are read.
+    fn read_id3v2_tag<R: Read + Seek>(f: &mut R) -> Result<(u8, u8, Vec<u8>), MetaError> {
+        let mut header = [0u8; 10];
+        f.read_exact(&mut header)?;
+        if &header[0..3] != b"ID3" {
+            return Err(MetaError::InvalidTag("ID3v2 header"));
+        }
+
+        let tag_size = synchsafe_to_u32(&header[6..10]) as usize;
+        let mut tag_data = Vec::new();
+        f.by_ref().take(tag_size as u64).read_to_end(&mut tag_data)?;
+        // a tag claiming more than the file holds means the file was cut short
+        if tag_data.len() < tag_size {
+            return Err(MetaError::Truncated { expected: tag_size, got: tag_data.len() });
+        }
+        let (major, flags) = (header[3], header[5]);
+        if major < 4 && flags & 0x80 != 0 {
+            tag_data = remove_unsynchronisation(&tag_data);
+        }
+        // an extended header sits between the tag header and the first frame;
+        // one claiming more than the tag holds leaves no frames to read
+        if major >= 3 && flags & 0x40 != 0 {
+            let ext_len = match tag_data.get(0..4) {
+                Some(size) => id3v2_extended_header_len(size, major),
+                None => tag_data.len(),
+            };
+            tag_data.drain(..ext_len.min(tag_data.len()));
+        }
+        Ok((major, flags, tag_data))
     }
 
     // --- FLAC (Vorbis comment) ---
-    fn from_flac(f: &mut File) -> io::Result<Self> {
+    fn from_flac<R: Read + Seek>(f: &mut R, tags: bool) -> Result<Self, MetaError> {
         let mut header = [0u8; 4];
         f.read_exact(&mut header)?;
         if &header != b"fLaC" {
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "not flac"));
+            return Err(MetaError::UnsupportedFormat);
         }
 
         let mut meta = SongMetadata::default();
@@ -227,7 +852,18 @@ impl SongMetadata {
             let block_len =
                 ((block_header[1] as u32) << 16) | ((block_header[2] as u32) << 8) | block_header[3] as u32;
 
-            if block_type == 4 {
+            if block_type == 0 {
+                let mut data = vec![0u8; block_len as usize];
+                f.read_exact(&mut data)?;
+                if data.len() >= 18 {
+                    let sample_rate = ((data[10] as u32) << 12) | ((data[11] as u32) << 4) | (data[12] as u32 >> 4);
+                    let channels = ((data[12] >> 1) & 0x07) + 1;
+                    let bits = (((data[12] & 0x01) << 4) | (data[13] >> 4)) + 1;
+                    meta.bitrate_kbps = pcm_bitrate_kbps(sample_rate, bits as u16, channels as u16);
+                    meta.sample_rate = Some(sample_rate).filter(|&r| r > 0);
+                    meta.channels = Some(channels);
+                }
+            } else if block_type == 4 && tags {
                 let mut data = vec![0u8; block_len as usize];
                 f.read_exact(&mut data)?;
                 parse_vorbis_comments(&mut meta, &data);
@@ -243,36 +879,441 @@ impl SongMetadata {
         Ok(meta)
     }
 
+    // --- Ogg Vorbis / Opus (Vorbis comment) ---
+    fn from_ogg<R: Read + Seek>(f: &mut R, tags: bool) -> Result<Self, MetaError> {
+        // identification header, then the comment header; the first alone
+        // tells Vorbis from Opus
+        f.seek(SeekFrom::Start(0))?;
+        let packets = ogg_packets(f, if tags { 2 } else { 1 })?;
+        match packets.first() {
+            Some(p) if p.starts_with(b"OpusHead") => return Ok(Self::from_opus(&packets)),
+            Some(p) if p.starts_with(b"\x01vorbis") => {}
+            _ => return Err(MetaError::UnsupportedFormat),
+        }
+
+        let mut meta = SongMetadata { format: AudioFormat::Vorbis, ..SongMetadata::default() };
+        if let Some(comments) = packets.get(1).and_then(|p| p.strip_prefix(b"\x03vorbis")) {
+            parse_vorbis_comments(&mut meta, comments);
+        }
+        Ok(meta)
+    }
+
+    /// Opus keeps its Vorbis-style comments in an `OpusTags` packet.
+    fn from_opus(packets: &[Vec<u8>]) -> Self {
+        let mut meta = SongMetadata { format: AudioFormat::Opus, ..SongMetadata::default() };
+        if let Some(comments) = packets.get(1).and_then(|p| p.strip_prefix(b"OpusTags")) {
+            parse_vorbis_comments(&mut meta, comments);
+        }
+        meta
+    }
+
     // --- M4A (MP4 atoms) ---
-    fn from_m4a(f: &mut File) -> io::Result<Self> {
+    fn from_m4a<S: Source>(f: &mut S, tags: bool) -> Result<Self, MetaError> {
         let mut meta = SongMetadata::default();
-        let mut data = Vec::new();
-        f.read_to_end(&mut data)?;
-        let mut i = 0;
-        while i + 8 <= data.len() {
-            let size = u32::from_be_bytes(data[i..i + 4].try_into().unwrap()) as usize;
-            if size < 8 || i + size > data.len() {
-                break;
-            }
-            let atom = &data[i + 4..i + 8];
+        let data = f.read_all()?;
+        let mut sound_check = None;
+        // the tag items live under `moov/udta/meta/ilst`
+        let ilst = find_m4a_atom(&data, b"ilst").filter(|_| tags).unwrap_or_default();
+        for (atom, body) in m4a_atoms(ilst) {
             if atom == b"\xa9nam" {
-                meta.title = extract_m4a_text(&data[i + 8..i + size]);
+                meta.title = extract_m4a_text(body);
             } else if atom == b"\xa9ART" {
-                meta.artist = extract_m4a_text(&data[i + 8..i + size]);
+                meta.artist = extract_m4a_text(body);
             } else if atom == b"\xa9alb" {
-                meta.album = extract_m4a_text(&data[i + 8..i + size]);
+                meta.album = extract_m4a_text(body);
+            } else if atom == b"aART" {
+                meta.album_artist = extract_m4a_text(body);
+            } else if atom == b"sonm" {
+                meta.title_sort = extract_m4a_text(body);
+            } else if atom == b"soar" {
+                meta.artist_sort = extract_m4a_text(body);
+            } else if atom == b"soal" {
+                meta.album_sort = extract_m4a_text(body);
+            } else if atom == b"\xa9wrt" {
+                meta.composer = extract_m4a_text(body);
             } else if atom == b"\xa9gen" {
-                meta.genre = extract_m4a_text(&data[i + 8..i + size]);
+                meta.genre = extract_m4a_text(body);
+            } else if atom == b"cpil" {
+                meta.compilation = extract_m4a_integer(body).map(|n| n != 0);
+            } else if atom == b"rtng" {
+                meta.rating = extract_m4a_integer(body).and_then(|r| u8::try_from(r).ok());
+            } else if atom == b"\xa9cmt" {
+                meta.comment = extract_m4a_text(body);
+            } else if atom == b"\xa9too" {
+                meta.encoder = extract_m4a_text(body);
+            } else if atom == b"\xa9lyr" {
+                meta.lyrics = extract_m4a_text(body);
+            } else if atom == b"\xa9day" {
+                meta.year = extract_m4a_text(body).and_then(|d| parse_year(&d));
+            } else if atom == b"trkn" || atom == b"disk" {
+                let (number, total) = extract_m4a_number_pair(body);
+                if atom == b"trkn" {
+                    (meta.track_number, meta.track_total) = (number, total);
+                } else {
+                    (meta.disc_number, meta.disc_total) = (number, total);
+                }
+            } else if atom == b"----"
+                && let Some((name, value)) = extract_m4a_freeform(body)
+            {
+                match name.to_ascii_lowercase().as_str() {
+                    "replaygain_track_gain" => meta.replay_gain_track_db = parse_gain_db(&value),
+                    "replaygain_album_gain" => meta.replay_gain_album_db = parse_gain_db(&value),
+                    "itunnorm" => sound_check = parse_itunnorm(&value),
+                    "rating" => meta.rating = value.trim().parse().ok(),
+                    "musicbrainz track id" => meta.musicbrainz_track_id = musicbrainz_id(&value),
+                    "musicbrainz album id" => meta.musicbrainz_album_id = musicbrainz_id(&value),
+                    "itunsmpb" => {
+                        if let Some((delay, padding)) = parse_itunsmpb(&value) {
+                            meta.encoder_delay = Some(delay);
+                            meta.encoder_padding = Some(padding);
+                        }
+                    }
+                    _ => {}
+                }
+                meta.extra.insert(name, value);
             }
-            i += size;
+        }
+        // the audio sample entry: channel count at 16, 16.16 sample rate at 24
+        if let Some(entry) = find_m4a_atom(&data, b"mp4a").filter(|e| e.len() >= 28) {
+            meta.channels = u8::try_from(u16::from_be_bytes([entry[16], entry[17]])).ok().filter(|&c| c > 0);
+            meta.sample_rate = Some(u16::from_be_bytes([entry[24], entry[25]]) as u32).filter(|&r| r > 0);
+        }
+        meta.bitrate_kbps = find_m4a_atom(&data, b"esds")
+            .and_then(esds_avg_bitrate)
+            .filter(|&bps| bps > 0)
+            .map(|bps| bps.div_ceil(1000));
+        // Sound Check only stands in when no real ReplayGain value was tagged
+        if meta.replay_gain_track_db.is_none() {
+            meta.replay_gain_track_db = sound_check;
         }
         Ok(meta)
     }
 
+    // --- Embedded pictures (APIC / PICTURE / covr) ---
+
+    /// The front cover (picture type 3) if tagged, else the first picture.
+    pub fn cover_art<P: AsRef<Path>>(path: P) -> Result<Option<CoverArt>, MetaError> {
+        let pictures = Self::loaded_pictures(path.as_ref(), &ParseOptions::default())?;
+        Ok(preferred_picture(pictures).map(CoverArt::from))
+    }
+
+    /// Every embedded picture in tag order: front and back covers, booklet
+    /// scans and so on, each with its picture type and description.
+    pub fn cover_arts<P: AsRef<Path>>(path: P) -> Result<Vec<CoverArt>, MetaError> {
+        Self::cover_arts_with(path, &ParseOptions::default())
+    }
+
+    /// Like [`SongMetadata::cover_arts`], but pictures larger than
+    /// `options.max_cover_bytes` are left out.
+    pub fn cover_arts_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Vec<CoverArt>, MetaError> {
+        let pictures = Self::loaded_pictures(path.as_ref(), options)?;
+        Ok(pictures.into_iter().map(CoverArt::from).collect())
+    }
+
+    /// Pictures whose image bytes were actually read (within the size cap).
+    fn loaded_pictures(path: &Path, options: &ParseOptions) -> Result<Vec<Picture>, MetaError> {
+        let mut f = File::open(path)?;
+        let mut pictures = Self::read_covers(&mut f, options.max_cover_bytes)?;
+        pictures.retain(|p| !p.is_skipped());
+        Ok(pictures)
+    }
+
+    /// Hashes the embedded cover image without handing the (possibly large)
+    /// image bytes back, so a library can detect tracks sharing the same
+    /// artwork and store it once. Returns `Ok(None)` when there is no cover.
+    pub fn cover_art_hash<P: AsRef<Path>>(path: P) -> Result<Option<u64>, MetaError> {
+        Self::cover_art_hash_with(path, &ParseOptions::default())
+    }
+
+    /// Like [`SongMetadata::cover_art_hash`], but covers larger than
+    /// `options.max_cover_bytes` are not read and yield `Ok(None)`.
+    pub fn cover_art_hash_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Option<u64>, MetaError> {
+        let pictures = Self::loaded_pictures(path.as_ref(), options)?;
+        Ok(preferred_picture(pictures).map(|p| fnv1a64(&p.data)))
+    }
+
+    /// Finds every embedded picture. Image bytes over `max_bytes` are never
+    /// loaded; only the MIME type and declared size are kept.
+    fn read_covers<R: Read + Seek>(f: &mut R, max_bytes: usize) -> Result<Vec<Picture>, MetaError> {
+        let mut header = Vec::new();
+        f.by_ref().take(8).read_to_end(&mut header)?;
+        f.seek(SeekFrom::Start(0))?;
+
+        // Ogg, AIFF, WAV and bare MP3 streams carry no pictures we read
+        match header.as_slice() {
+            [b'f', b'L', b'a', b'C', ..] => Self::flac_cover(f, max_bytes),
+            [b'I', b'D', b'3', ..] => Self::id3v2_cover(f, max_bytes),
+            [_, _, _, _, b'f', b't', b'y', b'p'] => Self::m4a_cover(f, max_bytes),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Walks the ID3v2 frames straight from the file so a frame claiming a
+    /// huge picture is skipped with a seek instead of being allocated. Frame
+    /// bodies go through `id3v2_frame_body`, as when reading tags.
+    fn id3v2_cover<R: Read + Seek>(f: &mut R, max_bytes: usize) -> Result<Vec<Picture>, MetaError> {
+        let mut header = [0u8; 10];
+        f.read_exact(&mut header)?;
+        if &header[0..3] != b"ID3" {
+            return Err(MetaError::InvalidTag("ID3v2 header"));
+        }
+        let tag_end = 10 + synchsafe_to_u32(&header[6..10]) as u64;
+        let (major, tag_flags) = (header[3], header[5]);
+
+        let mut pictures = Vec::new();
+        // ID3v2.2 `PIC` frames use a different layout and aren't read
+        if major < 3 {
+            return Ok(pictures);
+        }
+        // v2.3 unsynchronises the whole tag, frame headers included, so its
+        // frame sizes only hold once the tag is decoded
+        if major == 3 && tag_flags & 0x80 != 0 {
+            f.seek(SeekFrom::Start(0))?;
+            let (_, _, tag_data) = Self::read_id3v2_tag(f)?;
+            for (_, [_, format], frame) in id3v2_frames_with_flags(&tag_data, major).filter(|(id, ..)| *id == b"APIC") {
+                if let Some(body) = id3v2_frame_body(frame, major, format, tag_flags) {
+                    pictures.extend(parse_apic(&body, body.len(), max_bytes));
+                }
+            }
+            return Ok(pictures);
+        }
+        if header[5] & 0x40 != 0 {
+            let mut size = [0u8; 4];
+            f.read_exact(&mut size)?;
+            let ext_len = id3v2_extended_header_len(&size, header[3]) as u64;
+            f.seek(SeekFrom::Start(10 + ext_len))?;
+        }
+        let mut frame_header = [0u8; 10];
+        while f.stream_position()? + 10 <= tag_end {
+            if f.read(&mut frame_header)? != 10 {
+                break;
+            }
+            let size = id3v2_frame_size(&frame_header[4..8], major) as u64;
+            let start = f.stream_position()?;
+            if size == 0 || start + size > tag_end {
+                break;
+            }
+            if &frame_header[0..4] == b"APIC" {
+                let frame = Self::read_picture_block(f, size, max_bytes)?;
+                if let Some(body) = id3v2_frame_body(&frame, major, frame_header[9], tag_flags) {
+                    // only a fully read frame knows its decoded length
+                    let frame_len = if frame.len() as u64 == size { body.len() } else { size as usize };
+                    pictures.extend(parse_apic(&body, frame_len, max_bytes));
+                }
+            }
+            f.seek(SeekFrom::Start(start + size))?;
+        }
+        Ok(pictures)
+    }
+
+    fn flac_cover<R: Read + Seek>(f: &mut R, max_bytes: usize) -> Result<Vec<Picture>, MetaError> {
+        f.seek(SeekFrom::Start(4))?;
+
+        let mut pictures = Vec::new();
+        loop {
+            let mut block_header = [0u8; 4];
+            if f.read(&mut block_header)? != 4 {
+                break;
+            }
+            let last_block = (block_header[0] & 0x80) != 0;
+            let block_type = block_header[0] & 0x7F;
+            let block_len =
+                ((block_header[1] as u32) << 16) | ((block_header[2] as u32) << 8) | block_header[3] as u32;
+
+            if block_type == 6 {
+                let start = f.stream_position()?;
+                let block = Self::read_picture_block(f, block_len as u64, max_bytes)?;
+                pictures.extend(parse_flac_picture(&block, max_bytes));
+                f.seek(SeekFrom::Start(start + block_len as u64))?;
+            } else {
+                f.seek(SeekFrom::Current(block_len as i64))?;
+            }
+
+            if last_block {
+                break;
+            }
+        }
+
+        Ok(pictures)
+    }
+
+    /// `covr` may hold several `data` atoms; the first is treated as the
+    /// front cover and the rest as "other" pictures. The atoms down to
+    /// `moov/udta/meta/ilst/covr` are found by their headers, so only the
+    /// pictures themselves are read.
+    fn m4a_cover<R: Read + Seek>(f: &mut R, max_bytes: usize) -> Result<Vec<Picture>, MetaError> {
+        let mut body = (0, f.seek(SeekFrom::End(0))?);
+        for kind in [b"moov", b"udta", b"meta", b"ilst", b"covr"] {
+            let atoms = m4a_atom_headers(f, body.0, body.0 + body.1)?;
+            let Some(&(_, start, len)) = atoms.iter().find(|(k, ..)| k == kind) else {
+                return Ok(Vec::new());
+            };
+            // `meta` is a full box: 4 bytes of version/flags precede its children
+            body = if kind == b"meta" { (start + 4, len.saturating_sub(4)) } else { (start, len) };
+        }
+
+        let mut pictures = Vec::new();
+        for (_, start, len) in m4a_atom_headers(f, body.0, body.0 + body.1)?.into_iter().filter(|(k, ..)| k == b"data") {
+            f.seek(SeekFrom::Start(start))?;
+            let block = Self::read_picture_block(f, len, max_bytes)?;
+            let kind = if pictures.is_empty() { 3 } else { 0 };
+            pictures.extend(parse_m4a_cover_data(&block, len as usize, kind, max_bytes));
+        }
+        Ok(pictures)
+    }
+
+    /// Reads a whole picture frame/block, or only enough of its start to see
+    /// the MIME type and picture type when it is larger than `max_bytes`.
+    fn read_picture_block<R: Read>(f: &mut R, size: u64, max_bytes: usize) -> Result<Vec<u8>, MetaError> {
+        let wanted = if size > max_bytes.saturating_add(PICTURE_HEADER_PEEK) as u64 {
+            PICTURE_HEADER_PEEK as u64
+        } else {
+            size
+        };
+        let mut block = Vec::new();
+        f.take(wanted).read_to_end(&mut block)?;
+        Ok(block)
+    }
+
+    // --- Writing (ID3v2.4) ---
+
+    /// Rewrites the ID3v2 tag of an MP3 file from `title`, `artist(s)`,
+    /// `album`, `genre(s)` and the track number, as UTF-8 ID3v2.4 frames. A
+    /// field that is `None` removes its frame. Other frames of an existing
+    /// v2.3/v2.4 tag (pictures, comments, ...) are carried over, and the audio
+    /// after the old tag is left untouched. The new file is written next to
+    /// the original and renamed over it. Anything but MP3 yields
+    /// [`MetaError::UnsupportedFormat`].
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), MetaError> {
+        let path = path.as_ref();
+        let data = fs::read(path)?;
+        if !Self::is_mp3_data(&data) {
+            return Err(MetaError::UnsupportedFormat);
+        }
+
+        let mut body = Vec::new();
+        let title = self.title.as_deref().map(|t| vec![t]);
+        let artists = Self::tag_values(self.artist.as_ref(), &self.artists);
+        let album = self.album.as_deref().map(|a| vec![a]);
+        let genres = Self::tag_values(self.genre.as_ref(), &self.genres);
+        let track = match (self.track_number, self.track_total) {
+            (Some(n), Some(total)) => Some(format!("{n}/{total}")),
+            (Some(n), None) => Some(n.to_string()),
+            _ => None,
+        };
+        let track = track.as_deref().map(|t| vec![t]);
+        let written: [(&[u8], Option<Vec<&str>>); 5] =
+            [(b"TIT2", title), (b"TPE1", artists), (b"TALB", album), (b"TCON", genres), (b"TRCK", track)];
+        for (id, values) in &written {
+            if let Some(values) = values {
+                body.extend(id3v24_text_frame(id, values));
+            }
+        }
+
+        let mut audio_start = 0;
+        if data.starts_with(b"ID3") {
+            let mut cursor = Cursor::new(data.as_slice());
+            let (major, flags, tag_data) = Self::read_id3v2_tag(&mut cursor)?;
+            // an ID3v2.4 footer repeats the header after the frames
+            let footer = if major >= 4 && flags & 0x10 != 0 { 10 } else { 0 };
+            audio_start = (cursor.position() as usize + footer).min(data.len());
+            for (id, frame) in Self::kept_id3v2_frames(&tag_data, major, flags) {
+                if written.iter().all(|(w, _)| *w != id) {
+                    body.extend(id3v24_frame(id, &frame));
+                }
+            }
+        }
+
+        let size = u32::try_from(body.len()).ok().filter(|&s| s < 1 << 28).ok_or(MetaError::InvalidTag("ID3v2 tag size"))?;
+        let mut out = b"ID3\x04\x00\x00".to_vec();
+        out.extend_from_slice(&synchsafe_from_u32(size));
+        out.extend_from_slice(&body);
+        out.extend_from_slice(&data[audio_start..]);
+
+        // a sibling temp file renamed over the original, so a failed write
+        // never leaves a truncated file behind
+        let name = path.file_name().ok_or(MetaError::Io(io::ErrorKind::InvalidInput.into()))?;
+        let temp = path.with_file_name(format!(".{}.{}.tmp", name.to_string_lossy(), std::process::id()));
+        let written = fs::write(&temp, out)
+            .and_then(|_| fs::set_permissions(&temp, fs::metadata(path)?.permissions()))
+            .and_then(|_| fs::rename(&temp, path));
+        if written.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        Ok(written?)
+    }
+
+    /// Whether `data` is an MP3 file, judged from its framing alone so a
+    /// damaged tag doesn't stand in the way of rewriting it: a leading or
+    /// appended ID3v2 tag, an ID3v1 tag, or a frame header that two more
+    /// frames follow. Other containers are ruled out by their magic first,
+    /// since they may carry an ID3 tag too.
+    fn is_mp3_data(data: &[u8]) -> bool {
+        let container = matches!(data.get(0..4), Some(b"fLaC" | b"RIFF" | b"FORM" | b"OggS"))
+            || data.get(4..8) == Some(b"ftyp");
+        if container {
+            return false;
+        }
+        if data.starts_with(b"ID3") || data.len() >= 128 && data[data.len() - 128..].starts_with(b"TAG") {
+            return true;
+        }
+        if matches!(Self::appended_id3v2_start(&mut Cursor::new(data)), Ok(Some(_))) {
+            return true;
+        }
+        (0..data.len().saturating_sub(3)).any(|pos| {
+            let header = &data[pos..pos + 4];
+            header[0] == 0xFF
+                && mp3_frame_len(header, None).is_some_and(|len| {
+                    let to_eof = data.len().saturating_sub(pos + len) as u64;
+                    mp3_frames_follow(data, pos + len, header, None, to_eof)
+                })
+        })
+    }
+
+    /// Every multi-value entry while `single` is still its first value,
+    /// otherwise just `single` (the caller edited only that field).
+    fn tag_values<'a>(single: Option<&'a String>, all: &'a [String]) -> Option<Vec<&'a str>> {
+        let single = single?;
+        if all.first() == Some(single) {
+            Some(all.iter().map(String::as_str).collect())
+        } else {
+            Some(vec![single.as_str()])
+        }
+    }
+
+    /// Frames of an existing v2.3/v2.4 tag that can be copied into a v2.4
+    /// tag with cleared flags, as plain bodies (see `id3v2_frame_body`).
+    /// Compressed or encrypted frames are dropped since their flags don't
+    /// carry over, and v2.3 frames are renamed or dropped as v2.4 requires
+    /// (`TYER` becomes `TDRC`, `TDAT` is dropped, ...).
+    fn kept_id3v2_frames(tag: &[u8], major: u8, tag_flags: u8) -> Vec<(&[u8], Vec<u8>)> {
+        if major < 3 {
+            return Vec::new();
+        }
+        id3v2_frames_with_flags(tag, major)
+            .filter_map(|(id, [_, format], frame)| {
+                let id = if major == 3 { v23_to_v24_frame_id(id)? } else { id };
+                Some((id, id3v2_frame_body(frame, major, format, tag_flags)?.into_owned()))
+            })
+            .collect()
+    }
+
     // --- Duration extractors ---
 
-    /// WAV duration in milliseconds (uses byte_rate and data chunk)
-    fn wav_duration(f: &mut File) -> io::Result<u64> {
+    /// `duration_ms` as a [`Duration`]; the value is whole milliseconds.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration_ms.map(Duration::from_millis)
+    }
+
+    /// Sets `duration_ms` from an explicit sample count, e.g. after an editing
+    /// tool trims the audio. A zero sample rate clears the duration.
+    pub fn set_duration_from_samples(&mut self, total_samples: u64, sample_rate: u32) {
+        self.duration_ms = samples_to_ms(total_samples as u128, sample_rate);
+    }
+
+    /// WAV duration in milliseconds (uses byte_rate and data chunk).
+    /// Works on any seekable reader, so it can be recomputed after an edit.
+    pub fn compute_wav_duration<R: Read + Seek>(f: &mut R) -> Result<u64, MetaError> {
         f.seek(SeekFrom::Start(12))?;
 
         let mut fmt_found = false;
@@ -280,24 +1321,25 @@ impl SongMetadata {
         let mut data_size = 0u32;
 
         let mut buf = [0u8; 8];
+        let mut chunks = 0;
 
-        while f.read(&mut buf)? == 8 {
+        while chunks < IFF_MAX_CHUNKS && f.read(&mut buf)? == 8 {
+            chunks += 1;
             let id = &buf[0..4];
             let size = u32::from_le_bytes(buf[4..8].try_into().unwrap());
-            let next = f.seek(SeekFrom::Current(0))? + size as u64;
+            let next = f.stream_position()? + size as u64 + (size & 1) as u64;
 
             if id == b"fmt " {
-                let mut fmt = vec![0u8; size as usize];
-                f.read_exact(&mut fmt)?;
+                let mut fmt = Vec::new();
+                f.by_ref().take(size as u64).read_to_end(&mut fmt)?;
                 if fmt.len() >= 12 {
                     byte_rate = u32::from_le_bytes(fmt[8..12].try_into().unwrap());
                     fmt_found = true;
                 }
             } else if id == b"data" {
                 data_size = size;
-            } else {
-                f.seek(SeekFrom::Start(next))?;
             }
+            f.seek(SeekFrom::Start(next))?;
         }
 
         if fmt_found && byte_rate > 0 {
@@ -305,11 +1347,12 @@ impl SongMetadata {
             return Ok(duration_ms);
         }
 
-        Err(io::Error::new(io::ErrorKind::InvalidData, "No WAV duration"))
+        Err(MetaError::InvalidTag("WAV fmt chunk"))
     }
 
-    /// FLAC duration using STREAMINFO block (total samples / sample rate)
-    fn flac_duration(f: &mut File) -> io::Result<u64> {
+    /// FLAC duration in milliseconds using the STREAMINFO block
+    /// (total samples / sample rate).
+    pub fn compute_flac_duration<R: Read + Seek>(f: &mut R) -> Result<u64, MetaError> {
         f.seek(SeekFrom::Start(4))?;
 
         // iterate blocks until STREAMINFO (type 0)
@@ -327,7 +1370,7 @@ impl SongMetadata {
                 let mut data = vec![0; block_len as usize];
                 f.read_exact(&mut data)?;
                 if data.len() < 18 {
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, "STREAMINFO too small"));
+                    return Err(MetaError::Truncated { expected: 18, got: data.len() });
                 }
 
                 // sample rate: 20 bits (bits 0..19 of the composite field starting at data[10])
@@ -335,20 +1378,17 @@ impl SongMetadata {
                     | ((data[11] as u32) << 4)
                     | ((data[12] as u32 & 0xF0) >> 4);
 
-                // total samples: 36 bits (last 4 bits of data[12] and data[13..17])
+                // total samples: 36 bits (last 4 bits of data[13] and data[14..18]);
+                // the bits in between hold the channel count and bit depth
                 let total_samples =
-                    ((data[12] as u64 & 0x0F) << 32)
-                        | ((data[13] as u64) << 24)
-                        | ((data[14] as u64) << 16)
-                        | ((data[15] as u64) << 8)
-                        | (data[16] as u64);
-
-                if sample_rate == 0 {
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid sample rate"));
-                }
-
-                let duration_ms = (total_samples * 1000) / sample_rate as u64;
-                return Ok(duration_ms);
+                    ((data[13] as u64 & 0x0F) << 32)
+                        | ((data[14] as u64) << 24)
+                        | ((data[15] as u64) << 16)
+                        | ((data[16] as u64) << 8)
+                        | (data[17] as u64);
+
+                return samples_to_ms(total_samples as u128, sample_rate)
+                    .ok_or(MetaError::InvalidTag("STREAMINFO sample rate"));
             } else {
                 f.seek(SeekFrom::Current(block_len as i64))?;
             }
@@ -358,85 +1398,268 @@ impl SongMetadata {
             }
         }
 
-        Err(io::Error::new(io::ErrorKind::InvalidData, "No STREAMINFO"))
+        Err(MetaError::InvalidTag("STREAMINFO"))
     }
 
-    /// M4A/MP4 duration via `mvhd` atom (timescale + duration)
-    fn m4a_duration(f: &mut File) -> io::Result<u64> {
-        let mut data = Vec::new();
-        f.seek(SeekFrom::Start(0))?;
-        f.read_to_end(&mut data)?;
+    /// AIFF/AIFC duration in milliseconds from the `COMM` chunk (sample
+    /// frames over the 80-bit extended sample rate). Other chunks, the `SSND`
+    /// audio included, are skipped with a seek.
+    pub fn compute_aiff_duration<R: Read + Seek>(f: &mut R) -> Result<u64, MetaError> {
+        f.seek(SeekFrom::Start(12))?;
 
-        let mut i = 0usize;
-        while i + 8 <= data.len() {
-            let size = u32::from_be_bytes(data[i..i + 4].try_into().unwrap()) as usize;
-            if size < 8 || i + size > data.len() {
-                break;
-            }
-            if &data[i + 4..i + 8] == b"moov" {
-                // search for mvhd inside moov
-                let mut j = i + 8;
-                while j + 8 <= i + size {
-                    let sub_size = u32::from_be_bytes(data[j..j + 4].try_into().unwrap()) as usize;
-                    if sub_size < 8 || j + sub_size > data.len() {
-                        break;
-                    }
-                    if &data[j + 4..j + 8] == b"mvhd" {
-                        let version = data[j + 8];
-                        if version == 1 {
-                            // 64-bit duration: fields at j+24..j+28 timescale, j+28..j+36 duration
-                            if j + 36 > data.len() {
-                                return Err(io::Error::new(io::ErrorKind::InvalidData, "mvhd truncated"));
-                            }
-                            let timescale = u32::from_be_bytes(data[j + 24..j + 28].try_into().unwrap());
-                            let duration = u64::from_be_bytes(data[j + 28..j + 36].try_into().unwrap());
-                            if timescale == 0 {
-                                return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid timescale"));
-                            }
-                            return Ok((duration * 1000) / timescale as u64);
-                        } else {
-                            // version 0: 32-bit duration at j+24..j+28
-                            if j + 28 > data.len() {
-                                return Err(io::Error::new(io::ErrorKind::InvalidData, "mvhd truncated v0"));
-                            }
-                            let timescale = u32::from_be_bytes(data[j + 20..j + 24].try_into().unwrap());
-                            let duration = u32::from_be_bytes(data[j + 24..j + 28].try_into().unwrap()) as u64;
-                            if timescale == 0 {
-                                return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid timescale"));
-                            }
-                            return Ok((duration * 1000) / timescale as u64);
-                        }
+        let mut buf = [0u8; 8];
+        let mut chunks = 0;
+        while chunks < IFF_MAX_CHUNKS && f.read(&mut buf)? == 8 {
+            chunks += 1;
+            let chunk_size = u32::from_be_bytes(buf[4..8].try_into().unwrap()) as u64;
+            let next = f.stream_position()? + chunk_size + (chunk_size & 1);
+            if &buf[0..4] == b"COMM" {
+                let mut data = Vec::new();
+                f.by_ref().take(chunk_size.min(18)).read_to_end(&mut data)?;
+                if data.len() >= 18 {
+                    let frames = u32::from_be_bytes(data[2..6].try_into().unwrap());
+                    let rate = extended_to_f64(data[8..18].try_into().unwrap());
+                    if rate >= 1.0 {
+                        return Ok((frames as f64 * 1000.0 / rate) as u64);
                     }
-                    j += sub_size;
                 }
+                break;
+            }
+            f.seek(SeekFrom::Start(next))?;
+        }
+
+        Err(MetaError::InvalidTag("AIFF COMM chunk"))
+    }
+
+    /// M4A/MP4 duration in milliseconds via the `mvhd` atom (timescale + duration).
+    pub fn compute_m4a_duration<R: Read + Seek>(r: &mut R) -> Result<u64, MetaError> {
+        Self::m4a_duration_bytes(&read_to_vec(r)?)
+    }
+
+    /// Just the duration in milliseconds, without parsing any tags: the
+    /// format is sniffed from the header and only its duration helper runs.
+    /// MP3 files with a Xing/Info or VBRI header are answered from their
+    /// first frame instead of a scan of the whole file. `Ok(None)` when the
+    /// format is unknown or carries no usable duration.
+    pub fn duration_of<P: AsRef<Path>>(path: P) -> Result<Option<u64>, MetaError> {
+        let mut f = File::open(path)?;
+        let mut header = [0u8; 12];
+        if f.read(&mut header)? < 12 {
+            return Ok(None);
+        }
+        f.seek(SeekFrom::Start(0))?;
+
+        let duration = match &header[0..4] {
+            b"RIFF" if &header[8..12] == b"WAVE" => Self::compute_wav_duration(&mut f),
+            b"fLaC" => Self::compute_flac_duration(&mut f),
+            b"FORM" if &header[8..12] == b"AIFF" || &header[8..12] == b"AIFC" => Self::compute_aiff_duration(&mut f),
+            b"OggS" => Self::compute_ogg_duration(&mut f),
+            _ if &header[4..8] == b"ftyp" => Self::m4a_duration(&mut f),
+            _ => Self::mp3_quick_duration(&mut f),
+        };
+        match duration {
+            Ok(ms) => Ok(Some(ms)),
+            Err(MetaError::Io(e)) => Err(MetaError::Io(e)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Scans only the ID3v2 tag and the bytes after it when the first frame
+    /// is a Xing/Info or VBRI header; otherwise falls back to a full scan.
+    fn mp3_quick_duration<S: Source>(f: &mut S) -> Result<u64, MetaError> {
+        // comfortably more than the largest MPEG audio frame
+        const FIRST_FRAME_PEEK: u64 = 8 * 1024;
+
+        let mut header = [0u8; 10];
+        let tag_len = if f.read(&mut header)? == 10 && &header[0..3] == b"ID3" {
+            10 + synchsafe_to_u32(&header[6..10]) as u64
+        } else {
+            0
+        };
+        f.seek(SeekFrom::Start(0))?;
+        let mut start = Vec::new();
+        f.by_ref().take(tag_len + FIRST_FRAME_PEEK).read_to_end(&mut start)?;
+        let scan = Self::mp3_scan_bytes(&start);
+        if scan.vbr_header {
+            return scan.duration_ms();
+        }
+        let scan = Self::mp3_scan(f)?;
+        // neither a tag nor a single frame: not MP3, so no size-based estimate
+        if tag_len == 0 && scan.first_frame.is_none() {
+            return Err(MetaError::UnsupportedFormat);
+        }
+        scan.duration_ms()
+    }
+
+    /// MP3 duration in milliseconds from a frame scan of the whole stream.
+    pub fn compute_mp3_duration<R: Read + Seek>(r: &mut R) -> Result<u64, MetaError> {
+        Self::mp3_scan(r)?.duration_ms()
+    }
+
+    /// Ogg Vorbis/Opus duration in milliseconds: the granule position (a
+    /// sample count) of the stream's last page over the sample rate. Vorbis
+    /// gives its rate in the identification header; Opus always counts at
+    /// 48 kHz and its first `pre_skip` samples are decoder priming, not audio.
+    /// Only the first page and the end of the file are read.
+    pub fn compute_ogg_duration<R: Read + Seek>(f: &mut R) -> Result<u64, MetaError> {
+        let invalid = || MetaError::UnsupportedFormat;
+
+        // the identification packet always fits in the first page
+        f.seek(SeekFrom::Start(0))?;
+        let mut first = Vec::new();
+        f.by_ref().take(OGG_MAX_PAGE as u64).read_to_end(&mut first)?;
+        let (page, _) = ogg_page(&first).ok_or_else(invalid)?;
+        let id = page.body;
+        let (sample_rate, pre_skip) = if id.len() >= 16 && id.starts_with(b"\x01vorbis") {
+            (u32::from_le_bytes(id[12..16].try_into().unwrap()), 0)
+        } else if id.len() >= 12 && id.starts_with(b"OpusHead") {
+            (48_000, u16::from_le_bytes([id[10], id[11]]) as u64)
+        } else {
+            return Err(invalid());
+        };
+
+        let len = f.seek(SeekFrom::End(0))?;
+        f.seek(SeekFrom::Start(len.saturating_sub(OGG_MAX_PAGE as u64)))?;
+        let mut tail = Vec::new();
+        f.read_to_end(&mut tail)?;
+        let granule = ogg_last_granule(&tail, page.serial).ok_or_else(invalid)?;
+        samples_to_ms(granule.saturating_sub(pre_skip) as u128, sample_rate).ok_or_else(invalid)
+    }
+
+    fn m4a_duration<S: Source>(f: &mut S) -> Result<u64, MetaError> {
+        Self::m4a_duration_bytes(&f.read_all()?)
+    }
+
+    /// The movie duration from `mvhd`. When that is missing or zero, the
+    /// audio track's own `mdhd` media duration is used instead.
+    fn m4a_duration_bytes(data: &[u8]) -> Result<u64, MetaError> {
+        let movie = find_m4a_atom(data, b"mvhd")
+            .ok_or(MetaError::InvalidTag("mvhd"))
+            .and_then(|mvhd| Self::m4a_header_duration(mvhd, "mvhd timescale"));
+        if matches!(movie, Ok(ms) if ms > 0) {
+            return movie;
+        }
+        match find_m4a_audio_mdhd(data).map(|mdhd| Self::m4a_header_duration(mdhd, "mdhd timescale")) {
+            Some(Ok(ms)) if ms > 0 => Ok(ms),
+            _ => movie,
+        }
+    }
+
+    /// Duration in milliseconds from an `mvhd` or `mdhd` body, which share a
+    /// layout: version 1 widens the creation/modification times and the
+    /// duration to 64 bits, with the timescale between them.
+    fn m4a_header_duration(body: &[u8], what: &'static str) -> Result<u64, MetaError> {
+        let (timescale_at, duration_len) = if body.first() == Some(&1) { (20, 8) } else { (12, 4) };
+        let end = timescale_at + 4 + duration_len;
+        if body.len() < end {
+            return Err(MetaError::Truncated { expected: end, got: body.len() });
+        }
+        let timescale = u32::from_be_bytes(body[timescale_at..timescale_at + 4].try_into().unwrap());
+        let duration = body[timescale_at + 4..end].iter().fold(0u64, |n, &b| n << 8 | b as u64);
+        if timescale == 0 {
+            return Err(MetaError::InvalidTag(what));
+        }
+        Ok(duration.saturating_mul(1000) / timescale as u64)
+    }
+
+    /// Runs the frame scan and stores what it found (duration, CRC protection,
+    /// LAME gapless info).
+    fn apply_mp3_scan<S: Source>(&mut self, f: &mut S) {
+        if let Ok(scan) = Self::mp3_scan(f) {
+            self.duration_ms = scan.duration_ms().ok();
+            self.bitrate_kbps = scan.bitrate_kbps.or(self.bitrate_kbps);
+            if let Some((_, header)) = scan.first_frame {
+                self.sample_rate = Some(scan.sample_rate);
+                // channel mode 3 is single channel
+                self.channels = Some(if header[3] >> 6 == 3 { 1 } else { 2 });
+            }
+            self.crc_protected = scan.first_frame.map(|(_, header)| header[1] & 0x01 == 0);
+            if let Some((delay, padding)) = scan.gapless {
+                self.encoder_delay = Some(delay);
+                self.encoder_padding = Some(padding);
             }
-            i += size;
         }
+    }
+
+    /// Checks the CRC-16 of the first MPEG audio frame. Returns `Ok(None)`
+    /// when no frame is found or the stream doesn't carry CRCs (protection
+    /// bit set), otherwise whether the stored CRC matches the header and side
+    /// information. A mismatch usually means bit-rot in an archived file.
+    pub fn verify_mp3_crc<P: AsRef<Path>>(path: P) -> Result<Option<bool>, MetaError> {
+        let mut f = File::open(path)?;
+        let Some((offset, header)) = Self::mp3_scan(&mut f)?.first_frame else {
+            return Ok(None);
+        };
+        if header[1] & 0x01 != 0 {
+            return Ok(None);
+        }
+
+        let side_info_len = mpeg_side_info_len(&header);
 
-        Err(io::Error::new(io::ErrorKind::NotFound, "No m4a duration"))
+        // header (4) + stored CRC (2) + side information
+        let mut frame = vec![0u8; 6 + side_info_len];
+        f.seek(SeekFrom::Start(offset as u64))?;
+        f.read_exact(&mut frame)?;
+        let stored = u16::from_be_bytes([frame[4], frame[5]]);
+
+        let mut covered = frame[2..4].to_vec();
+        covered.extend_from_slice(&frame[6..]);
+        Ok(Some(crc16_mpeg(&covered) == stored))
+    }
+
+    /// A seek table for an MP3 file: the byte offset of every audio frame
+    /// paired with the number of samples before it (encoder delay included),
+    /// so a time maps to the last frame at or before `ms * sample_rate / 1000`.
+    /// The Xing/Info or VBRI header frame is left out. The table holds one
+    /// entry per frame (about 38 per second), so only build it for files that
+    /// need precise seeking.
+    pub fn mp3_frame_index<P: AsRef<Path>>(path: P) -> Result<Vec<(u64, u32)>, MetaError> {
+        let mut f = File::open(path)?;
+        let mut index = Vec::new();
+        Self::mp3_scan_frames(&mut f, Some(&mut index))?;
+        Ok(index)
     }
 
     /// MP3 duration: lenient frame scanning that handles VBR/CBR by parsing frames.
     /// This implementation:
     /// - skips ID3v2 tag if present
     /// - then searches for frame sync (0xFFE) and parses headers
+    /// - stops at the first frame when it carries a Xing/Info or VBRI header,
+    ///   whose frame count gives the duration directly
+    /// - measures free-format frames (bitrate index 0) from the distance to the
+    ///   next header, then reuses that length
+    /// - only trusts a frame found outside a run of frames when two more frames
+    ///   (or the end of the stream, or a tag running to it) follow, so false
+    ///   syncs aren't counted
     /// - is lenient: if an invalid header is encountered, advance by 1 byte and continue
-    /// - sums total samples and derives duration by (total_samples / sample_rate)
-    fn mp3_duration(f: &mut File) -> io::Result<u64> {
-        use std::cmp::min;
-
-        let total_size = f.metadata()?.len();
+    /// - sums total samples; `Mp3Scan::duration_ms` derives (total_samples / sample_rate)
+    /// - streams the file through a sliding window, so memory use doesn't grow
+    ///   with its length
+    fn mp3_scan<R: Read + Seek>(f: &mut R) -> Result<Mp3Scan, MetaError> {
+        Ok(Self::mp3_scan_frames(f, None)?)
+    }
 
-        // read whole file into memory chunk-by-chunk for scanning
-        f.seek(SeekFrom::Start(0))?;
-        let mut all = Vec::with_capacity(min(total_size as usize, 16_000_000));
-        f.read_to_end(&mut all)?;
+    fn mp3_scan_bytes(all: &[u8]) -> Mp3Scan {
+        Self::mp3_scan_frames(&mut Cursor::new(all), None).expect("reading from memory can't fail")
+    }
 
+    /// The frame scan behind [`mp3_scan`](Self::mp3_scan). With `index`,
+    /// every audio frame is recorded as it is passed, and a Xing/Info or VBRI
+    /// header doesn't end the scan early.
+    fn mp3_scan_frames<R: Read + Seek>(r: &mut R, mut index: Option<&mut Vec<(u64, u32)>>) -> io::Result<Mp3Scan> {
+        // enough past a frame start for the largest frame (free format at
+        // 640 kbps and 8 kHz), the next header and a trailing tag marker
+        const LOOKAHEAD: usize = 8 * 1024;
+
+        let total_size = r.seek(SeekFrom::End(0))?;
+        r.seek(SeekFrom::Start(0))?;
+        let mut window = SlidingWindow::new(r);
         let mut pos = 0usize;
 
         // skip ID3v2 if present
-        if all.len() >= 10 && &all[0..3] == b"ID3" {
-            let tag_size = synchsafe_to_u32(&all[6..10]) as usize;
+        let head = window.at(0, 10)?;
+        if head.len() >= 10 && &head[0..3] == b"ID3" {
+            let tag_size = synchsafe_to_u32(&head[6..10]) as usize;
             pos = 10 + tag_size;
         }
 
@@ -446,23 +1669,35 @@ impl SongMetadata {
 
         let mut total_samples: u128 = 0;
         let mut last_sample_rate: u32 = 0;
+        let mut first_frame = None;
+        // bitrate of the first audio frame while every frame matches it
+        let mut constant_bitrate: Option<Option<u32>> = None;
+        let mut audio_bytes = 0u64;
+        let mut gapless = None;
+        let mut vbr_header = false;
+        // unpadded length shared by every free-format frame, once measured
+        let mut free_format_len = None;
+        // where the last accepted frame ends; a frame starting there is trusted
+        let mut chained_at = None;
 
         // To avoid pathological loops, set a max iterations proportional to file size.
-        let max_iterations = all.len() * 2;
+        let max_iterations = total_size as usize * 2;
 
         let mut iterations = 0usize;
-        while pos + 4 <= all.len() && iterations < max_iterations {
+        while iterations < max_iterations {
+            // the stream from `pos` on, as far as the window reaches
+            let all = window.at(pos, LOOKAHEAD)?;
+            if all.len() < 4 {
+                break;
+            }
             iterations += 1;
 
-            let b1 = all[pos];
-            let b2 = all[pos + 1];
+            let b1 = all[0];
+            let b2 = all[1];
 
             // sync: 11 bits set -> first byte 0xFF and top 3 bits of second are 1 (0xE0)
             if b1 == 0xFF && (b2 & 0xE0) == 0xE0 {
-                if pos + 4 > all.len() {
-                    break;
-                }
-                let header = &all[pos..pos+4];
+                let header = &all[0..4];
                 let version_bits = (header[1] >> 3) & 0x03;
                 let layer_bits = (header[1] >> 1) & 0x03;
                 let bitrate_index = (header[2] >> 4) & 0x0F;
@@ -534,21 +1769,35 @@ impl SongMetadata {
                     bitrate_table_mpeg2_layer3.get(bitrate_index as usize).copied().unwrap_or(0)
                 };
 
-                if bitrate_kbps == 0 || sample_rate == 0 {
-                    // invalid header values; skip 1 byte and continue (lenient)
-                    pos += 1;
-                    continue;
-                }
-
                 // compute frame length in bytes for Layer III
                 // formula:
                 // MPEG1 Layer III: frame_size = floor(144000 * bitrate_kbps / sample_rate) + padding
                 // MPEG2/2.5 Layer III: frame_size = floor(72000 * bitrate_kbps / sample_rate) + padding
-                let frame_size = if mpeg_version == 1.0  {
-                    ((144000u32 * bitrate_kbps) / sample_rate) + padding
+                let size_factor = if mpeg_version == 1.0 { 144000u32 } else { 72000u32 };
+
+                let (bitrate_kbps, frame_size) = if bitrate_index == 0 {
+                    // free format: a fixed bitrate the header doesn't state (at
+                    // most 640 kbps). Rare, but some encoders still write it, so
+                    // the frame length is measured from the first frame instead.
+                    let len = match free_format_len {
+                        Some(len) => len,
+                        None => {
+                            let max_len = (size_factor * 640 / sample_rate) as usize;
+                            let Some(len) = mp3_free_format_len(all, 0, max_len) else {
+                                pos += 1;
+                                continue;
+                            };
+                            *free_format_len.insert(len)
+                        }
+                    };
+                    (len as u32 * sample_rate / size_factor, len + padding as usize)
+                } else if bitrate_kbps == 0 || sample_rate == 0 {
+                    // invalid header values; skip 1 byte and continue (lenient)
+                    pos += 1;
+                    continue;
                 } else {
-                    ((72000u32 * bitrate_kbps) / sample_rate) + padding
-                } as usize;
+                    (bitrate_kbps, ((size_factor * bitrate_kbps) / sample_rate + padding) as usize)
+                };
 
                 if frame_size == 0 {
                     pos += 1;
@@ -563,13 +1812,54 @@ impl SongMetadata {
                 };
 
                 // Sanity: ensure we won't overflow and that frame fits
-                if pos + frame_size > all.len() {
+                if frame_size > all.len() {
                     // If frame would extend past EOF, break
                     // but still add the final partial frame's samples proportionally? We'll stop.
                     break;
                 }
 
+                // outside a run of frames, a sync can be chance bytes in
+                // album art or junk: only accept it if the next two frames
+                // are where this one says they will be
+                let to_eof = total_size.saturating_sub((pos + frame_size) as u64);
+                if chained_at != Some(pos) && !mp3_frames_follow(all, frame_size, header, free_format_len, to_eof) {
+                    pos += 1;
+                    continue;
+                }
+                chained_at = Some(pos + frame_size);
+
                 // accumulate
+                if first_frame.is_none() {
+                    first_frame = Some((pos, [header[0], header[1], header[2], header[3]]));
+                    // the Xing/Info/VBRI frame itself holds no audio
+                    let frame = &all[..frame_size];
+                    if let Some(frames) = xing_frame_count(frame).or_else(|| vbri_frame_count(frame)) {
+                        if index.is_some() {
+                            pos += frame_size;
+                            continue;
+                        }
+                        total_samples = frames as u128 * samples_per_frame as u128;
+                        // priming and padding samples aren't part of the audio
+                        gapless = lame_gapless(frame);
+                        if let Some((delay, padding)) = gapless {
+                            total_samples = total_samples.saturating_sub(delay as u128 + padding as u128);
+                        }
+                        last_sample_rate = sample_rate;
+                        audio_bytes = total_size.saturating_sub((pos + frame_size) as u64);
+                        constant_bitrate = Some(None);
+                        vbr_header = true;
+                        break;
+                    }
+                }
+                constant_bitrate = match constant_bitrate {
+                    None => Some(Some(bitrate_kbps)),
+                    Some(Some(kbps)) if kbps != bitrate_kbps => Some(None),
+                    unchanged => unchanged,
+                };
+                if let Some(index) = index.as_deref_mut() {
+                    index.push((pos as u64, u32::try_from(total_samples).unwrap_or(u32::MAX)));
+                }
+                audio_bytes += frame_size as u64;
                 total_samples += samples_per_frame as u128;
                 last_sample_rate = sample_rate;
                 // advance by frame_size
@@ -580,16 +1870,51 @@ impl SongMetadata {
             }
         }
 
+        // VBR: average over the audio bytes; bits per millisecond is kbps
+        let bitrate_kbps = constant_bitrate.and_then(|cbr| {
+            cbr.or_else(|| {
+                let ms = samples_to_ms(total_samples, last_sample_rate).filter(|&ms| ms > 0)?;
+                u32::try_from(audio_bytes * 8 / ms).ok()
+            })
+        });
+
+        Ok(Mp3Scan {
+            total_samples,
+            sample_rate: last_sample_rate,
+            file_size: total_size,
+            first_frame,
+            bitrate_kbps,
+            gapless,
+            vbr_header,
+        })
+    }
+}
+
+/// What a pass over the MPEG audio frames found.
+struct Mp3Scan {
+    total_samples: u128,
+    sample_rate: u32,
+    file_size: u64,
+    /// Offset and header bytes of the first accepted frame.
+    first_frame: Option<(usize, [u8; 4])>,
+    bitrate_kbps: Option<u32>,
+    /// Encoder delay and padding from a LAME tag.
+    gapless: Option<(u16, u16)>,
+    /// Whether the frame count came from a Xing/Info or VBRI header.
+    vbr_header: bool,
+}
+
+impl Mp3Scan {
+    fn duration_ms(&self) -> Result<u64, MetaError> {
+        let total_samples = self.total_samples;
+        let last_sample_rate = self.sample_rate;
+        let total_size = self.file_size;
+
         // If we parsed frames and have a sample rate, compute duration
-        if total_samples > 0 && last_sample_rate > 0 {
-            let duration_ms = (total_samples * 1000u128) / (last_sample_rate as u128);
-            // clamp to u64
-            let duration_u64 = if duration_ms > u128::from(u64::MAX) {
-                u64::MAX
-            } else {
-                duration_ms as u64
-            };
-            return Ok(duration_u64);
+        if total_samples > 0
+            && let Some(duration_ms) = samples_to_ms(total_samples, last_sample_rate)
+        {
+            return Ok(duration_ms);
         }
 
         // fallback: estimate using file size and a typical bitrate (128kbps)
@@ -600,7 +1925,7 @@ impl SongMetadata {
             return Ok(duration_ms);
         }
 
-        Err(io::Error::new(io::ErrorKind::InvalidData, "Could not determine MP3 duration"))
+        Err(MetaError::InvalidTag("MPEG audio frames"))
     }
 }
 
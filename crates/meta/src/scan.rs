@@ -0,0 +1,38 @@
+use crate::{MetaError, SongMetadata};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Parses every file with [`SongMetadata::from_file`] on a pool of `threads`
+/// worker threads (at least one). Each file is parsed independently, so the
+/// work is embarrassingly parallel; the output keeps the order of `paths`.
+pub fn scan_all(paths: &[PathBuf], threads: usize) -> Vec<(PathBuf, Result<SongMetadata, MetaError>)> {
+    let (job_tx, job_rx) = mpsc::channel();
+    for index in 0..paths.len() {
+        job_tx.send(index).unwrap();
+    }
+    drop(job_tx);
+    let job_rx = Arc::new(Mutex::new(job_rx));
+    let (result_tx, result_rx) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, paths.len().max(1)) {
+            let job_rx = Arc::clone(&job_rx);
+            let result_tx = result_tx.clone();
+            scope.spawn(move || {
+                loop {
+                    // taken in its own statement so the lock isn't held while parsing
+                    let job = job_rx.lock().unwrap().recv();
+                    let Ok(index) = job else { break };
+                    let _ = result_tx.send((index, SongMetadata::from_file(&paths[index])));
+                }
+            });
+        }
+    });
+    drop(result_tx);
+
+    let mut results: Vec<_> = result_rx.into_iter().collect();
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(index, result)| (paths[index].clone(), result)).collect()
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_aiff_text_chunks_and_duration() {
+    // odd-length NAME exercises the pad byte before the next chunk
+    let bytes = aiff_file(&[
+        aiff_comm(88_200),
+        aiff_chunk(b"NAME", b"Take 3"),
+        aiff_chunk(b"AUTH", b"Studio Band"),
+        aiff_chunk(b"ANNO", b"rough mix"),
+        aiff_chunk(b"(c) ", b"2004 Studio Band"),
+        aiff_chunk(b"SSND", &[0u8; 64]),
+    ]);
+    let path = write_temp("daw", "aiff", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Take 3"));
+    assert_eq!(meta.artist.as_deref(), Some("Studio Band"));
+    assert_eq!(meta.comment.as_deref(), Some("rough mix"));
+    assert_eq!(meta.copyright.as_deref(), Some("2004 Studio Band"));
+    assert_eq!(meta.duration_ms, Some(2_000));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_aiff_without_text_chunks() {
+    let path = write_temp("bare", "aif", &aiff_file(&[aiff_comm(44_100)]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.artist, None);
+    assert_eq!(meta.duration_ms, Some(1_000));
+    fs::remove_file(path).unwrap();
+}
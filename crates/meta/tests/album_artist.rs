@@ -0,0 +1,45 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn read(name: &str, ext: &str, bytes: &[u8]) -> SongMetadata {
+    let path = write_temp(name, ext, bytes);
+    let meta = SongMetadata::from_file(&path).unwrap();
+    fs::remove_file(path).unwrap();
+    meta
+}
+
+#[test]
+fn test_tpe2_is_not_copied_into_artist() {
+    let meta = read("tpe2", "mp3", &mp3_file(&[id3_text(b"TPE2", "Various Artists")]));
+    assert_eq!(meta.album_artist.as_deref(), Some("Various Artists"));
+    assert_eq!(meta.artist, None);
+
+    let meta = read("tpe1_tpe2", "mp3", &mp3_file(&[
+        id3_text(b"TPE1", "Track Artist"),
+        id3_text(b"TPE2", "Various Artists"),
+    ]));
+    assert_eq!(meta.artist.as_deref(), Some("Track Artist"));
+    assert_eq!(meta.album_artist.as_deref(), Some("Various Artists"));
+}
+
+#[test]
+fn test_vorbis_album_artist_spellings() {
+    for comment in ["ALBUMARTIST=Various Artists", "ALBUM ARTIST=Various Artists"] {
+        let meta = read("vorbis_aa", "flac", &flac_file(&[(4, vorbis_comments(&[comment]))]));
+        assert_eq!(meta.album_artist.as_deref(), Some("Various Artists"), "{comment}");
+    }
+}
+
+#[test]
+fn test_m4a_aart() {
+    let meta = read("aart", "m4a", &m4a_file(&[
+        atom(b"aART", &data_atom(1, b"Various Artists")),
+        atom(b"\xa9ART", &data_atom(1, b"Track Artist")),
+    ]));
+    assert_eq!(meta.album_artist.as_deref(), Some("Various Artists"));
+    assert_eq!(meta.artist.as_deref(), Some("Track Artist"));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::*;
+use meta::{AudioFormat, SongMetadata};
+
+use std::fs;
+
+/// An ID3v2.4 tag with the footer flag set, followed by its `3DI` footer.
+fn appended_tag(frames: &[Vec<u8>]) -> Vec<u8> {
+    let body = frames.concat();
+    let mut tag = id3v2_tag(4, 0x10, &body);
+    tag.extend_from_slice(b"3DI\x04\0\x10");
+    tag.extend_from_slice(&synchsafe(body.len() as u32));
+    tag
+}
+
+fn text(id: &[u8; 4], value: &str) -> Vec<u8> {
+    id3v24_frame(id, &[&[3u8][..], value.as_bytes()].concat())
+}
+
+#[test]
+fn test_tag_appended_after_audio() {
+    let bytes = [mp3_frames(5, false), appended_tag(&[text(b"TIT2", "Stream Rip"), text(b"TPE1", "DJ")])].concat();
+    let path = write_temp("appended_tag", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.format, AudioFormat::Mp3);
+    assert_eq!((meta.title.as_deref(), meta.artist.as_deref()), (Some("Stream Rip"), Some("DJ")));
+    assert!(meta.duration_ms.is_some());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_appended_tag_before_id3v1() {
+    let bytes = [
+        mp3_frames(5, false),
+        appended_tag(&[text(b"TIT2", "Full Title From ID3v2")]),
+        id3v1_tag("Full Title", "V1 Artist", "Album", "2001", 3, 17),
+    ].concat();
+    let path = write_temp("appended_tag_v1", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    // the ID3v2 tag wins; ID3v1 fills what it lacks
+    assert_eq!(meta.title.as_deref(), Some("Full Title From ID3v2"));
+    assert_eq!(meta.artist.as_deref(), Some("V1 Artist"));
+    assert_eq!(meta.track_number, Some(3));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_stray_footer_magic_is_ignored() {
+    let mut bytes = mp3_frames(5, false);
+    bytes.extend_from_slice(b"3DI\x04\0\x10\0\0\0\x05");
+    let path = write_temp("stray_footer", "mp3", &bytes);
+    assert_eq!(SongMetadata::from_file(&path).unwrap().format, AudioFormat::Mp3);
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn read(name: &str, ext: &str, bytes: &[u8]) -> SongMetadata {
+    let path = write_temp(name, ext, bytes);
+    let meta = SongMetadata::from_file(&path).unwrap();
+    fs::remove_file(path).unwrap();
+    meta
+}
+
+fn properties(meta: &SongMetadata) -> (Option<u32>, Option<u8>) {
+    (meta.sample_rate, meta.channels)
+}
+
+#[test]
+fn test_wav_and_flac_properties() {
+    let wav = [b"WAVE".to_vec(), riff_chunk(b"fmt ", &wav_fmt(1, 22_050, 16)), riff_chunk(b"data", &[0; 64])].concat();
+    let wav = riff_chunk(b"RIFF", &wav);
+    assert_eq!(properties(&read("props", "wav", &wav)), (Some(22_050), Some(1)));
+
+    let flac = flac_file(&[]);
+    assert_eq!(properties(&read("props", "flac", &flac)), (Some(44_100), Some(2)));
+}
+
+#[test]
+fn test_mp3_properties_from_first_frame() {
+    let mut stereo = mp3_file(&[id3_text(b"TIT2", "Stereo")]);
+    stereo.extend(mp3_frames(5, false));
+    assert_eq!(properties(&read("props_stereo", "mp3", &stereo)), (Some(44_100), Some(2)));
+
+    let mut mono = mp3_frames(5, false);
+    for frame in mono.chunks_mut(MP3_FRAME_LEN) {
+        frame[3] |= 0xC0;
+    }
+    let mono = [mp3_file(&[id3_text(b"TIT2", "Mono")]), mono].concat();
+    assert_eq!(properties(&read("props_mono", "mp3", &mono)), (Some(44_100), Some(1)));
+}
+
+#[test]
+fn test_m4a_sample_entry_properties() {
+    let mut bytes = m4a_file(&[atom(b"\xa9nam", &data_atom(1, b"AAC"))]);
+    bytes.extend(atom(b"moov", &m4a_audio_track(1, 48_000, 96_000)));
+    assert_eq!(properties(&read("props", "m4a", &bytes)), (Some(48_000), Some(1)));
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn bitrate_of(name: &str, ext: &str, bytes: &[u8]) -> Option<u32> {
+    let path = write_temp(name, ext, bytes);
+    let kbps = SongMetadata::from_file(&path).unwrap().bitrate_kbps;
+    fs::remove_file(path).unwrap();
+    kbps
+}
+
+#[test]
+fn test_mp3_cbr_and_vbr_bitrate() {
+    let mut cbr = mp3_file(&[id3_text(b"TIT2", "CBR")]);
+    cbr.extend(mp3_frames(20, false));
+    assert_eq!(bitrate_of("cbr", "mp3", &cbr), Some(128));
+
+    // equal numbers of 128 and 192 kbps frames average to about 160
+    let mut vbr = mp3_file(&[id3_text(b"TIT2", "VBR")]);
+    for _ in 0..20 {
+        vbr.extend(mp3_frame_at(9));
+        vbr.extend(mp3_frame_at(11));
+    }
+    let kbps = bitrate_of("vbr", "mp3", &vbr).unwrap();
+    assert!((158..=162).contains(&kbps), "{kbps}");
+}
+
+#[test]
+fn test_mp3_xing_average_bitrate() {
+    // 100 frames of 128 kbps audio declared in the Xing header
+    let mut bytes = xing_frame(b"Xing", 100);
+    bytes.extend(mp3_frames(100, false));
+    let kbps = bitrate_of("xing_rate", "mp3", &bytes).unwrap();
+    assert!((127..=129).contains(&kbps), "{kbps}");
+}
+
+#[test]
+fn test_pcm_bitrates() {
+    assert_eq!(bitrate_of("flac_rate", "flac", &flac_file(&[])), Some(1411));
+    let wav = wav_file(&[], 1_000);
+    assert_eq!(bitrate_of("wav_rate", "wav", &wav), Some(1411));
+}
+
+#[test]
+fn test_m4a_esds_bitrate() {
+    let mut bytes = m4a_file(&[atom(b"\xa9nam", &data_atom(1, b"AAC"))]);
+    let mut mvhd = [0u8; 96];
+    mvhd[8..12].copy_from_slice(&1_000u32.to_be_bytes());
+    mvhd[12..16].copy_from_slice(&5_000u32.to_be_bytes());
+    let moov = [full_atom(b"mvhd", &mvhd), m4a_audio_track(2, 44_100, 256_000)].concat();
+    bytes.extend(atom(b"moov", &moov));
+    assert_eq!(bitrate_of("esds", "m4a", &bytes), Some(256));
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::*;
+use meta::{load_cache_from, save_cache_to, scan_with_cache_in, SongMetadata};
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+fn cache_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("meta_cache_{}_{}", name, std::process::id())).join("scan.json")
+}
+
+fn set_modified(path: &PathBuf, time: SystemTime) {
+    File::options().write(true).open(path).unwrap().set_modified(time).unwrap();
+}
+
+#[test]
+fn test_cache_round_trip() {
+    let cache = cache_path("round_trip");
+    assert!(load_cache_from(&cache).unwrap().is_empty());
+
+    let path = write_temp("cache_song", "mp3", &mp3_file(&[id3_text(b"TIT2", "Song")]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    save_cache_to(&cache, &[(path.clone(), meta)]).unwrap();
+
+    let entries = load_cache_from(&cache).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].path.is_absolute());
+    assert!(entries[0].modified.is_some());
+    assert_eq!(entries[0].metadata.title.as_deref(), Some("Song"));
+
+    fs::remove_file(path).unwrap();
+    fs::remove_dir_all(cache.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_scan_with_cache_skips_unchanged_files() {
+    let cache = cache_path("skip");
+    let path = write_temp("cache_skip", "mp3", &mp3_file(&[id3_text(b"TIT2", "First")]));
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    set_modified(&path, mtime);
+
+    let first = scan_with_cache_in(&cache, std::slice::from_ref(&path), 2).unwrap();
+    assert_eq!(first[0].1.as_ref().unwrap().title.as_deref(), Some("First"));
+
+    // new contents behind an unchanged mtime: the cached entry is used
+    fs::write(&path, mp3_file(&[id3_text(b"TIT2", "Second")])).unwrap();
+    set_modified(&path, mtime);
+    let cached = scan_with_cache_in(&cache, std::slice::from_ref(&path), 2).unwrap();
+    assert_eq!(cached[0].1.as_ref().unwrap().title.as_deref(), Some("First"));
+
+    // a new mtime invalidates it
+    set_modified(&path, mtime + Duration::from_secs(60));
+    let fresh = scan_with_cache_in(&cache, std::slice::from_ref(&path), 2).unwrap();
+    assert_eq!(fresh[0].1.as_ref().unwrap().title.as_deref(), Some("Second"));
+
+    fs::remove_file(path).unwrap();
+    fs::remove_dir_all(cache.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_scan_with_cache_keeps_order_and_errors() {
+    let cache = cache_path("order");
+    let a = write_temp("cache_a", "mp3", &mp3_file(&[id3_text(b"TIT2", "A")]));
+    let b = write_temp("cache_b", "mp3", &mp3_file(&[id3_text(b"TIT2", "B")]));
+    let missing = PathBuf::from("/nonexistent/cache.mp3");
+
+    // warm the cache with `b` only
+    scan_with_cache_in(&cache, std::slice::from_ref(&b), 1).unwrap();
+    let results = scan_with_cache_in(&cache, &[a.clone(), missing.clone(), b.clone()], 2).unwrap();
+    assert_eq!(results.iter().map(|(p, _)| p.clone()).collect::<Vec<_>>(), [a.clone(), missing, b.clone()]);
+    assert_eq!(results[0].1.as_ref().unwrap().title.as_deref(), Some("A"));
+    assert!(results[1].1.is_err());
+    assert_eq!(results[2].1.as_ref().unwrap().title.as_deref(), Some("B"));
+    assert_eq!(load_cache_from(&cache).unwrap().len(), 2);
+
+    fs::remove_file(a).unwrap();
+    fs::remove_file(b).unwrap();
+    fs::remove_dir_all(cache.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_scan_with_cache_keeps_other_entries() {
+    let cache = cache_path("keep");
+    let a = write_temp("cache_keep_a", "mp3", &mp3_file(&[id3_text(b"TIT2", "A")]));
+    let b = write_temp("cache_keep_b", "mp3", &mp3_file(&[id3_text(b"TIT2", "B")]));
+
+    scan_with_cache_in(&cache, std::slice::from_ref(&a), 1).unwrap();
+    scan_with_cache_in(&cache, std::slice::from_ref(&b), 1).unwrap();
+    let entries = load_cache_from(&cache).unwrap();
+    let titles: Vec<_> = entries.iter().map(|e| e.metadata.title.as_deref().unwrap()).collect();
+    assert_eq!(titles, ["B", "A"]);
+    assert_eq!(entries[0].modified, Some(fs::metadata(&b).unwrap().modified().unwrap()));
+
+    fs::remove_file(a).unwrap();
+    fs::remove_file(b).unwrap();
+    fs::remove_dir_all(cache.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_scan_with_cache_reports_write_errors() {
+    let blocker = write_temp("cache_blocker", "txt", b"not a directory");
+    let path = write_temp("cache_unwritable", "mp3", &mp3_file(&[id3_text(b"TIT2", "Song")]));
+
+    assert!(scan_with_cache_in(&blocker.join("scan.json"), std::slice::from_ref(&path), 1).is_err());
+
+    fs::remove_file(blocker).unwrap();
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn comm(encoding: u8, description: &[u8], text: &[u8]) -> Vec<u8> {
+    let mut body = vec![encoding];
+    body.extend_from_slice(b"eng");
+    body.extend_from_slice(description);
+    body.extend_from_slice(text);
+    id3_frame(b"COMM", &body)
+}
+
+fn utf16le(s: &str) -> Vec<u8> {
+    let mut out = vec![0xFF, 0xFE];
+    out.extend(s.encode_utf16().flat_map(u16::to_le_bytes));
+    out
+}
+
+#[test]
+fn test_id3v2_comm_skips_itunes_data() {
+    let path = write_temp("comm", "mp3", &mp3_file(&[
+        comm(0, b"iTunNORM\0", b" 0000044E 00000000"),
+        comm(1, &[utf16le(""), vec![0, 0]].concat(), &utf16le("Recorded live")),
+        comm(3, b"\0", b"a later comment"),
+    ]));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().comment.as_deref(), Some("Recorded live"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_comment_from_vorbis_m4a_and_wav() {
+    let flac = write_temp("comment", "flac", &flac_file(&[(4, vorbis_comments(&["COMMENT=Recorded live"]))]));
+    let ogg = write_temp("comment", "ogg", &ogg_vorbis_file(44_100, &["DESCRIPTION=Recorded live"], 44_100));
+    let m4a = write_temp("comment", "m4a", &m4a_file(&[atom(b"\xa9cmt", &data_atom(1, b"Recorded live"))]));
+    let info = riff_chunk(b"LIST", &[b"INFO".to_vec(), riff_chunk(b"ICMT", b"Recorded live\0")].concat());
+    let wav = write_temp("comment", "wav", &wav_file(&[info], 1_000));
+    for path in [flac, ogg, m4a, wav] {
+        assert_eq!(SongMetadata::from_file(&path).unwrap().comment.as_deref(), Some("Recorded live"));
+        fs::remove_file(path).unwrap();
+    }
+}
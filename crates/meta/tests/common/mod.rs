@@ -0,0 +1,465 @@
+//! Byte-level builders for hand-crafted audio fixtures.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Writes `bytes` to a per-process temp file and returns its path.
+pub fn write_temp(name: &str, ext: &str, bytes: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("meta_{}_{}.{}", name, std::process::id(), ext));
+    fs::write(&path, bytes).expect("Failed to write fixture");
+    path
+}
+
+// --- MP4 ---
+
+pub fn atom(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    out
+}
+
+/// An atom whose body starts with 4 bytes of version/flags.
+pub fn full_atom(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut with_flags = vec![0u8; 4];
+    with_flags.extend_from_slice(body);
+    atom(kind, &with_flags)
+}
+
+/// A `data` atom with the given type flag.
+pub fn data_atom(type_flag: u32, value: &[u8]) -> Vec<u8> {
+    let mut body = type_flag.to_be_bytes().to_vec();
+    body.extend_from_slice(&[0, 0, 0, 0]);
+    body.extend_from_slice(value);
+    atom(b"data", &body)
+}
+
+pub fn freeform(name: &str, value: &str) -> Vec<u8> {
+    let mut body = full_atom(b"mean", b"com.apple.iTunes");
+    body.extend(full_atom(b"name", name.as_bytes()));
+    body.extend(data_atom(1, value.as_bytes()));
+    atom(b"----", &body)
+}
+
+/// A complete file with the given `ilst` children nested under
+/// `moov/udta/meta/ilst`, as iTunes writes them.
+pub fn m4a_file(ilst_items: &[Vec<u8>]) -> Vec<u8> {
+    let ilst = atom(b"ilst", &ilst_items.concat());
+    let meta = full_atom(b"meta", &ilst);
+    let udta = atom(b"udta", &meta);
+    let moov = atom(b"moov", &udta);
+    let mut bytes = atom(b"ftyp", b"M4A \0\0\0\0");
+    bytes.extend(moov);
+    bytes
+}
+
+/// A `moov/trak/.../stsd` audio track whose `mp4a` entry declares
+/// `channels` and `sample_rate`, with an `esds` average bitrate in bps.
+pub fn m4a_audio_track(channels: u16, sample_rate: u16, avg_bitrate: u32) -> Vec<u8> {
+    let mut config = vec![0x40, 0x15, 0, 0, 0];
+    config.extend_from_slice(&(avg_bitrate + 64_000).to_be_bytes()); // max bitrate
+    config.extend_from_slice(&avg_bitrate.to_be_bytes());
+    let mut es = vec![0, 1, 0, 0x04, config.len() as u8];
+    es.extend(config);
+    let mut descriptors = vec![0x03, 0x80, 0x80, 0x80, es.len() as u8];
+    descriptors.extend(es);
+    let esds = full_atom(b"esds", &descriptors);
+
+    let mut entry = vec![0u8; 16]; // reserved, data reference index, reserved
+    entry.extend_from_slice(&channels.to_be_bytes());
+    entry.extend_from_slice(&16u16.to_be_bytes());
+    entry.extend_from_slice(&[0; 4]);
+    entry.extend_from_slice(&sample_rate.to_be_bytes());
+    entry.extend_from_slice(&[0, 0]); // 16.16 fixed point fraction
+    entry.extend(esds);
+    let stsd = full_atom(b"stsd", &[&1u32.to_be_bytes()[..], &atom(b"mp4a", &entry)].concat());
+
+    let stbl = atom(b"stbl", &stsd);
+    let minf = atom(b"minf", &stbl);
+    let mdia = atom(b"mdia", &minf);
+    atom(b"trak", &mdia)
+}
+
+// --- ID3v2 ---
+
+/// An ID3v2.3 frame with a plain 32-bit size.
+pub fn id3_frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    out.extend_from_slice(&[0, 0]);
+    out.extend_from_slice(body);
+    out
+}
+
+/// An ID3v2.4 frame, whose size is synchsafe.
+pub fn id3v24_frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend_from_slice(&synchsafe(body.len() as u32));
+    out.extend_from_slice(&[0, 0]);
+    out.extend_from_slice(body);
+    out
+}
+
+/// An ID3v2.2 frame: 3-character ID and 3-byte size.
+pub fn id3v22_frame(id: &[u8; 3], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    out.extend_from_slice(body);
+    out
+}
+
+/// A Latin-1 text frame.
+pub fn id3_text(id: &[u8; 4], text: &str) -> Vec<u8> {
+    let mut body = vec![0u8];
+    body.extend_from_slice(text.as_bytes());
+    id3_frame(id, &body)
+}
+
+pub fn apic(kind: u8, mime: &str, image: &[u8]) -> Vec<u8> {
+    let mut body = vec![0u8];
+    body.extend_from_slice(mime.as_bytes());
+    body.push(0);
+    body.push(kind);
+    body.extend_from_slice(b"cover\0");
+    body.extend_from_slice(image);
+    id3_frame(b"APIC", &body)
+}
+
+/// Applies ID3v2 unsynchronisation: a `0x00` is inserted after every `0xFF`.
+pub fn unsynchronise(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    for &b in data {
+        out.push(b);
+        if b == 0xFF {
+            out.push(0);
+        }
+    }
+    out
+}
+
+pub fn synchsafe(n: u32) -> [u8; 4] {
+    [(n >> 21) as u8 & 0x7F, (n >> 14) as u8 & 0x7F, (n >> 7) as u8 & 0x7F, n as u8 & 0x7F]
+}
+
+/// An ID3v2 tag (`major` version, header `flags`) around `body`.
+pub fn id3v2_tag(major: u8, flags: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![b'I', b'D', b'3', major, 0, flags];
+    out.extend_from_slice(&synchsafe(body.len() as u32));
+    out.extend_from_slice(body);
+    out
+}
+
+/// An ID3v2.3 tagged file with no audio frames.
+pub fn mp3_file(frames: &[Vec<u8>]) -> Vec<u8> {
+    id3v2_tag(3, 0, &frames.concat())
+}
+
+// --- FLAC ---
+
+pub fn flac_block(block_type: u8, last: bool, body: &[u8]) -> Vec<u8> {
+    let len = body.len() as u32;
+    let mut out = vec![block_type | if last { 0x80 } else { 0 }, (len >> 16) as u8, (len >> 8) as u8, len as u8];
+    out.extend_from_slice(body);
+    out
+}
+
+/// STREAMINFO for `total_samples` at `sample_rate`, stereo, 16-bit.
+pub fn flac_streaminfo(sample_rate: u32, total_samples: u64) -> Vec<u8> {
+    let mut body = vec![0u8; 34];
+    body[10] = (sample_rate >> 12) as u8;
+    body[11] = (sample_rate >> 4) as u8;
+    // channels and bits per sample are stored minus one
+    let channels_minus_one: u8 = 1;
+    let bits_minus_one: u8 = 15;
+    body[12] = ((sample_rate & 0x0F) << 4) as u8 | (channels_minus_one << 1) | (bits_minus_one >> 4);
+    body[13] = ((bits_minus_one & 0x0F) << 4) | ((total_samples >> 32) & 0x0F) as u8;
+    body[14..18].copy_from_slice(&(total_samples as u32).to_be_bytes());
+    body
+}
+
+pub fn vorbis_comments(comments: &[&str]) -> Vec<u8> {
+    let vendor = b"fixture";
+    let mut out = (vendor.len() as u32).to_le_bytes().to_vec();
+    out.extend_from_slice(vendor);
+    out.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for c in comments {
+        out.extend_from_slice(&(c.len() as u32).to_le_bytes());
+        out.extend_from_slice(c.as_bytes());
+    }
+    out
+}
+
+pub fn flac_picture(kind: u32, mime: &str, image: &[u8]) -> Vec<u8> {
+    let mut out = kind.to_be_bytes().to_vec();
+    out.extend_from_slice(&(mime.len() as u32).to_be_bytes());
+    out.extend_from_slice(mime.as_bytes());
+    out.extend_from_slice(&5u32.to_be_bytes());
+    out.extend_from_slice(b"cover");
+    out.extend_from_slice(&[0u8; 16]);
+    out.extend_from_slice(&(image.len() as u32).to_be_bytes());
+    out.extend_from_slice(image);
+    out
+}
+
+/// A FLAC file: STREAMINFO followed by `blocks` (type, body).
+pub fn flac_file(blocks: &[(u8, Vec<u8>)]) -> Vec<u8> {
+    let mut out = b"fLaC".to_vec();
+    out.extend(flac_block(0, blocks.is_empty(), &flac_streaminfo(44_100, 441_000)));
+    for (i, (kind, body)) in blocks.iter().enumerate() {
+        out.extend(flac_block(*kind, i + 1 == blocks.len(), body));
+    }
+    out
+}
+
+// --- MPEG audio ---
+
+/// Size of one MPEG-1 Layer III frame at 128 kbps / 44.1 kHz without padding.
+pub const MP3_FRAME_LEN: usize = 417;
+
+/// `count` silent MPEG-1 Layer III frames (128 kbps, 44.1 kHz, stereo).
+/// Protected frames carry a valid CRC-16 after the header.
+pub fn mp3_frames(count: usize, protected: bool) -> Vec<u8> {
+    let mut frame = vec![0u8; MP3_FRAME_LEN];
+    frame[0..4].copy_from_slice(&[0xFF, if protected { 0xFA } else { 0xFB }, 0x90, 0x00]);
+    if protected {
+        // CRC covers the last two header bytes and the 32-byte side info
+        let mut covered = frame[2..4].to_vec();
+        covered.extend_from_slice(&frame[6..38]);
+        frame[4..6].copy_from_slice(&crc16_mpeg(&covered).to_be_bytes());
+    }
+    frame.repeat(count)
+}
+
+/// An unprotected frame like [`mp3_frames`] carrying a Xing/Info header
+/// (`tag`) with the frame-count field set to `frames`.
+pub fn xing_frame(tag: &[u8; 4], frames: u32) -> Vec<u8> {
+    let mut frame = mp3_frames(1, false);
+    // after the 4-byte header and 32 bytes of stereo MPEG-1 side info
+    frame[36..40].copy_from_slice(tag);
+    frame[40..44].copy_from_slice(&1u32.to_be_bytes());
+    frame[44..48].copy_from_slice(&frames.to_be_bytes());
+    frame
+}
+
+/// An unprotected frame like [`mp3_frames`] carrying a Fraunhofer VBRI
+/// header that declares `frames` frames.
+pub fn vbri_frame(frames: u32) -> Vec<u8> {
+    let mut frame = mp3_frames(1, false);
+    frame[36..40].copy_from_slice(b"VBRI");
+    frame[40..42].copy_from_slice(&1u16.to_be_bytes());
+    frame[46..50].copy_from_slice(&(frames * MP3_FRAME_LEN as u32).to_be_bytes());
+    frame[50..54].copy_from_slice(&frames.to_be_bytes());
+    frame
+}
+
+/// One unprotected MPEG-1 Layer III frame at 44.1 kHz with the given
+/// bitrate index (9 = 128 kbps, 10 = 160 kbps, 11 = 192 kbps).
+pub fn mp3_frame_at(bitrate_index: u8) -> Vec<u8> {
+    let kbps = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320][bitrate_index as usize];
+    let mut frame = vec![0u8; 144_000 * kbps / 44_100];
+    frame[0..4].copy_from_slice(&[0xFF, 0xFB, bitrate_index << 4, 0x00]);
+    frame
+}
+
+pub fn crc16_mpeg(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in data {
+        for bit in (0..8).rev() {
+            let carry = ((crc >> 15) & 1) ^ ((byte as u16 >> bit) & 1);
+            crc <<= 1;
+            if carry != 0 {
+                crc ^= 0x8005;
+            }
+        }
+    }
+    crc
+}
+
+// --- ID3v1 ---
+
+fn padded(text: &str, len: usize) -> Vec<u8> {
+    let mut out = text.as_bytes()[..text.len().min(len)].to_vec();
+    out.resize(len, 0);
+    out
+}
+
+/// A 128-byte ID3v1.1 tag.
+pub fn id3v1_tag(title: &str, artist: &str, album: &str, year: &str, track: u8, genre: u8) -> Vec<u8> {
+    let mut out = b"TAG".to_vec();
+    out.extend(padded(title, 30));
+    out.extend(padded(artist, 30));
+    out.extend(padded(album, 30));
+    out.extend(padded(year, 4));
+    out.extend(padded("", 28));
+    out.push(0);
+    out.push(track);
+    out.push(genre);
+    out
+}
+
+/// A 227-byte enhanced `TAG+` block carrying the text after the first 30
+/// characters of each field.
+pub fn id3v1_enhanced_tag(title: &str, artist: &str, album: &str, genre: &str) -> Vec<u8> {
+    let tail = |s: &str| s.get(30..).unwrap_or("").to_string();
+    let mut out = b"TAG+".to_vec();
+    out.extend(padded(&tail(title), 60));
+    out.extend(padded(&tail(artist), 60));
+    out.extend(padded(&tail(album), 60));
+    out.push(0);
+    out.extend(padded(genre, 30));
+    out.extend(padded("000:00", 6));
+    out.extend(padded("004:10", 6));
+    out
+}
+
+// --- RIFF/WAVE ---
+
+/// A RIFF chunk, padded to an even length.
+pub fn riff_chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+/// A PCM `fmt ` chunk body.
+pub fn wav_fmt(channels: u16, sample_rate: u32, bits: u16) -> Vec<u8> {
+    let block_align = channels * bits / 8;
+    let mut out = 1u16.to_le_bytes().to_vec();
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&bits.to_le_bytes());
+    out
+}
+
+/// A WAVE file: `fmt ` (stereo, 44.1 kHz, 16-bit), the given chunks, then
+/// `data_len` bytes of silence.
+pub fn wav_file(chunks: &[Vec<u8>], data_len: usize) -> Vec<u8> {
+    let mut body = b"WAVE".to_vec();
+    body.extend(riff_chunk(b"fmt ", &wav_fmt(2, 44_100, 16)));
+    for c in chunks {
+        body.extend_from_slice(c);
+    }
+    body.extend(riff_chunk(b"data", &vec![0u8; data_len]));
+    let mut out = b"RIFF".to_vec();
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend(body);
+    out
+}
+
+/// Writes `text` into a `len`-byte field padded with `pad`.
+pub fn fixed_field(text: &str, len: usize, pad: u8) -> Vec<u8> {
+    let mut out = text.as_bytes().to_vec();
+    out.resize(len, pad);
+    out
+}
+
+/// A `bext` chunk body (version 1, no coding history).
+pub fn bext_body(description: &str, originator: &str, date: &str, time: &str, time_reference: u64) -> Vec<u8> {
+    let mut out = fixed_field(description, 256, 0);
+    out.extend(fixed_field(originator, 32, b' '));
+    out.extend(fixed_field("REF-001", 32, b' '));
+    out.extend(fixed_field(date, 10, 0));
+    out.extend(fixed_field(time, 8, 0));
+    out.extend_from_slice(&time_reference.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&[0u8; 64 + 190]);
+    out
+}
+
+// --- Ogg ---
+
+pub const OGG_SERIAL: u32 = 0x1234;
+
+/// An Ogg page holding `data`. When `complete` the final packet ends on this
+/// page; otherwise `data` must be a multiple of 255 bytes and continues on
+/// the next page.
+pub fn ogg_page(header_type: u8, granule: u64, packets: &[&[u8]], complete: bool) -> Vec<u8> {
+    let mut lacing = Vec::new();
+    for p in packets {
+        lacing.extend(std::iter::repeat_n(255u8, p.len() / 255));
+        if complete || p.len() % 255 != 0 {
+            lacing.push((p.len() % 255) as u8);
+        }
+    }
+    let mut out = b"OggS\0".to_vec();
+    out.push(header_type);
+    out.extend_from_slice(&granule.to_le_bytes());
+    out.extend_from_slice(&OGG_SERIAL.to_le_bytes());
+    out.extend_from_slice(&[0; 8]); // sequence number and CRC, not checked
+    out.push(lacing.len() as u8);
+    out.extend(lacing);
+    for p in packets {
+        out.extend_from_slice(p);
+    }
+    out
+}
+
+pub fn vorbis_id_header(sample_rate: u32) -> Vec<u8> {
+    let mut out = b"\x01vorbis".to_vec();
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.push(2);
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&[0; 13]);
+    out
+}
+
+/// An Ogg Vorbis file: identification and comment headers, then one audio
+/// page ending at `total_samples`.
+pub fn ogg_vorbis_file(sample_rate: u32, comments: &[&str], total_samples: u64) -> Vec<u8> {
+    let comment_packet = [&b"\x03vorbis"[..], &vorbis_comments(comments), &[1]].concat();
+    let mut out = ogg_page(0x02, 0, &[&vorbis_id_header(sample_rate)], true);
+    out.extend(ogg_page(0, 0, &[&comment_packet, b"\x05vorbis setup"], true));
+    out.extend(ogg_page(0x04, total_samples, &[&[0u8; 100]], true));
+    out
+}
+
+/// An Ogg Opus file: `OpusHead` with `pre_skip`, `OpusTags`, then one audio
+/// page ending at granule `end_granule` (48 kHz).
+pub fn ogg_opus_file(pre_skip: u16, comments: &[&str], end_granule: u64) -> Vec<u8> {
+    let mut head = b"OpusHead\x01\x02".to_vec();
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&44_100u32.to_le_bytes()); // original input rate, informational
+    head.extend_from_slice(&[0, 0, 0]);
+    let tags = [&b"OpusTags"[..], &vorbis_comments(comments)].concat();
+    let mut out = ogg_page(0x02, 0, &[&head], true);
+    out.extend(ogg_page(0, 0, &[&tags], true));
+    out.extend(ogg_page(0x04, end_granule, &[&[0u8; 100]], true));
+    out
+}
+
+// --- AIFF ---
+
+/// A big-endian IFF chunk, padded to an even length.
+pub fn aiff_chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    out.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+/// A `COMM` chunk for 16-bit stereo at 44.1 kHz.
+pub fn aiff_comm(sample_frames: u32) -> Vec<u8> {
+    let mut body = 2u16.to_be_bytes().to_vec();
+    body.extend_from_slice(&sample_frames.to_be_bytes());
+    body.extend_from_slice(&16u16.to_be_bytes());
+    body.extend_from_slice(&[0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]);
+    aiff_chunk(b"COMM", &body)
+}
+
+pub fn aiff_file(chunks: &[Vec<u8>]) -> Vec<u8> {
+    let body = [b"AIFF".to_vec(), chunks.concat()].concat();
+    let mut out = b"FORM".to_vec();
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    out.extend(body);
+    out
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_compilation_from_every_format() {
+    let mp3 = write_temp("compilation", "mp3", &mp3_file(&[id3_text(b"TCMP", "1")]));
+    let flac = write_temp("compilation", "flac", &flac_file(&[(4, vorbis_comments(&["COMPILATION=1"]))]));
+    let m4a = write_temp("compilation", "m4a", &m4a_file(&[atom(b"cpil", &data_atom(21, &[1]))]));
+    for path in [mp3, flac, m4a] {
+        assert_eq!(SongMetadata::from_file(&path).unwrap().compilation, Some(true));
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_compilation_false_and_unknown() {
+    let off = write_temp("compilation_off", "mp3", &mp3_file(&[id3_text(b"TCMP", "0")]));
+    let m4a = write_temp("compilation_off", "m4a", &m4a_file(&[atom(b"cpil", &data_atom(21, &[0]))]));
+    for path in [off, m4a] {
+        assert_eq!(SongMetadata::from_file(&path).unwrap().compilation, Some(false));
+        fs::remove_file(path).unwrap();
+    }
+
+    let garbled = write_temp("compilation_text", "flac", &flac_file(&[(4, vorbis_comments(&["COMPILATION=maybe"]))]));
+    let untagged = write_temp("compilation_none", "mp3", &mp3_file(&[id3_text(b"TIT2", "Song")]));
+    for path in [garbled, untagged] {
+        assert_eq!(SongMetadata::from_file(&path).unwrap().compilation, None);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_composer_from_every_format() {
+    let mp3 = write_temp("composer", "mp3", &mp3_file(&[
+        id3_text(b"TPE1", "Berliner Philharmoniker"),
+        id3_text(b"TCOM", "Ludwig van Beethoven"),
+    ]));
+    let flac = write_temp("composer", "flac", &flac_file(&[(4, vorbis_comments(&[
+        "ARTIST=Berliner Philharmoniker",
+        "COMPOSER=Ludwig van Beethoven",
+    ]))]));
+    let m4a = write_temp("composer", "m4a", &m4a_file(&[
+        atom(b"\xa9ART", &data_atom(1, b"Berliner Philharmoniker")),
+        atom(b"\xa9wrt", &data_atom(1, b"Ludwig van Beethoven")),
+    ]));
+    for path in [mp3, flac, m4a] {
+        let meta = SongMetadata::from_file(&path).unwrap();
+        assert_eq!(meta.composer.as_deref(), Some("Ludwig van Beethoven"));
+        assert_eq!(meta.artist.as_deref(), Some("Berliner Philharmoniker"));
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_composer_absent() {
+    let path = write_temp("no_composer", "mp3", &mp3_file(&[id3_text(b"TIT2", "Song")]));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().composer, None);
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,251 @@
+mod common;
+
+use common::*;
+use meta::{ParseOptions, SongMetadata};
+
+use std::fs;
+
+const FRONT: &[u8] = b"\x89PNG front cover bytes";
+const BACK: &[u8] = b"\xFF\xD8\xFF back cover bytes";
+
+#[test]
+fn test_cover_art_hash_matches_across_formats() {
+    let mp3 = write_temp("cover_hash", "mp3", &mp3_file(&[
+        id3_text(b"TIT2", "Song"),
+        apic(4, "image/jpeg", BACK),
+        apic(3, "image/png", FRONT),
+    ]));
+    let flac = write_temp("cover_hash", "flac", &flac_file(&[(6, flac_picture(3, "image/png", FRONT))]));
+    let m4a = write_temp("cover_hash", "m4a", &m4a_file(&[atom(b"covr", &data_atom(14, FRONT))]));
+
+    let mp3_hash = SongMetadata::cover_art_hash(&mp3).unwrap();
+    assert!(mp3_hash.is_some());
+    assert_eq!(SongMetadata::cover_art_hash(&flac).unwrap(), mp3_hash);
+    assert_eq!(SongMetadata::cover_art_hash(&m4a).unwrap(), mp3_hash);
+
+    for path in [mp3, flac, m4a] {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_cover_art_hash_distinguishes_images() {
+    let front = write_temp("cover_front", "flac", &flac_file(&[(6, flac_picture(3, "image/png", FRONT))]));
+    let back = write_temp("cover_back", "flac", &flac_file(&[(6, flac_picture(4, "image/jpeg", BACK))]));
+    assert_ne!(
+        SongMetadata::cover_art_hash(&front).unwrap(),
+        SongMetadata::cover_art_hash(&back).unwrap()
+    );
+    fs::remove_file(front).unwrap();
+    fs::remove_file(back).unwrap();
+}
+
+#[test]
+fn test_cover_art_hash_without_cover() {
+    let path = write_temp("no_cover", "mp3", &mp3_file(&[id3_text(b"TIT2", "Song")]));
+    assert_eq!(SongMetadata::cover_art_hash(&path).unwrap(), None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cover_art_hash_respects_size_cap() {
+    let image = vec![0xABu8; 10_000];
+    let mp3 = write_temp("cover_cap", "mp3", &mp3_file(&[apic(3, "image/jpeg", &image)]));
+    let flac = write_temp("cover_cap", "flac", &flac_file(&[(6, flac_picture(3, "image/jpeg", &image))]));
+    let m4a = write_temp("cover_cap", "m4a", &m4a_file(&[atom(b"covr", &data_atom(13, &image))]));
+
+    let small = ParseOptions { max_cover_bytes: 1_000, ..ParseOptions::default() };
+    let large = ParseOptions { max_cover_bytes: 10_000, ..ParseOptions::default() };
+    for path in [&mp3, &flac, &m4a] {
+        assert_eq!(SongMetadata::cover_art_hash_with(path, &small).unwrap(), None);
+        assert!(SongMetadata::cover_art_hash_with(path, &large).unwrap().is_some());
+    }
+
+    assert!(SongMetadata::cover_arts_with(&m4a, &small).unwrap().is_empty());
+
+    fs::remove_file(mp3).unwrap();
+    fs::remove_file(flac).unwrap();
+    fs::remove_file(m4a).unwrap();
+}
+
+#[test]
+fn test_oversized_picture_claim_is_not_allocated() {
+    // an APIC frame claiming ~200 MB inside a tag that claims the same
+    let mut frame = b"APIC".to_vec();
+    frame.extend_from_slice(&200_000_000u32.to_be_bytes());
+    frame.extend_from_slice(&[0, 0, 0]);
+    frame.extend_from_slice(b"image/png\0\x03\0tiny");
+    let mut tag = id3v2_tag(3, 0, &frame);
+    tag[6..10].copy_from_slice(&synchsafe(200_000_100));
+    let path = write_temp("cover_claim", "mp3", &tag);
+
+    assert_eq!(SongMetadata::cover_art_hash(&path).unwrap(), None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cover_arts_lists_every_picture() {
+    let mp3 = write_temp("cover_all", "mp3", &mp3_file(&[
+        apic(4, "image/jpeg", BACK),
+        apic(3, "image/png", FRONT),
+    ]));
+    let flac = write_temp("cover_all", "flac", &flac_file(&[
+        (6, flac_picture(4, "image/jpeg", BACK)),
+        (6, flac_picture(3, "image/png", FRONT)),
+    ]));
+
+    for path in [&mp3, &flac] {
+        let covers = SongMetadata::cover_arts(path).unwrap();
+        assert_eq!(covers.len(), 2);
+        assert_eq!((covers[0].kind, covers[0].mime.as_str(), covers[0].data.as_slice()), (4, "image/jpeg", BACK));
+        assert_eq!(covers[1].description, "cover");
+
+        let front = SongMetadata::cover_art(path).unwrap().unwrap();
+        assert_eq!((front.kind, front.data.as_slice()), (3, FRONT));
+    }
+
+    fs::remove_file(mp3).unwrap();
+    fs::remove_file(flac).unwrap();
+}
+
+#[test]
+fn test_m4a_covr_with_several_images() {
+    let covr = [data_atom(14, FRONT), data_atom(13, BACK)].concat();
+    let path = write_temp("cover_all", "m4a", &m4a_file(&[atom(b"covr", &covr)]));
+
+    let covers = SongMetadata::cover_arts(&path).unwrap();
+    assert_eq!(covers.len(), 2);
+    assert_eq!((covers[0].kind, covers[0].mime.as_str(), covers[0].data.as_slice()), (3, "image/png", FRONT));
+    assert_eq!((covers[1].kind, covers[1].mime.as_str(), covers[1].data.as_slice()), (0, "image/jpeg", BACK));
+    assert_eq!(SongMetadata::cover_art(&path).unwrap().unwrap().data, FRONT);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cover_art_without_pictures() {
+    let path = write_temp("cover_none", "flac", &flac_file(&[]));
+    assert!(SongMetadata::cover_arts(&path).unwrap().is_empty());
+    assert_eq!(SongMetadata::cover_art(&path).unwrap(), None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_apic_in_id3v24_tag() {
+    // large enough that the synchsafe and plain size encodings differ
+    let image = vec![0x5Au8; 1_000];
+    let mut body = b"\0image/jpeg\0\x03front\0".to_vec();
+    body.extend_from_slice(&image);
+    let mut title = vec![0u8];
+    title.extend_from_slice(b"Song");
+    let tag = id3v2_tag(4, 0, &[id3v24_frame(b"APIC", &body), id3v24_frame(b"TIT2", &title)].concat());
+    let path = write_temp("cover_v24", "mp3", &tag);
+
+    let cover = SongMetadata::cover_art(&path).unwrap().unwrap();
+    assert_eq!((cover.kind, cover.mime.as_str(), cover.description.as_str()), (3, "image/jpeg", "front"));
+    assert_eq!(cover.data, image);
+    assert_eq!(SongMetadata::from_file(&path).unwrap().title.as_deref(), Some("Song"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_flac_picture_fields() {
+    let mut block = flac_picture(3, "image/png", FRONT);
+    // width and height sit right after the 5-byte "cover" description
+    let fields_at = 8 + "image/png".len() + 4 + 5;
+    block[fields_at..fields_at + 4].copy_from_slice(&600u32.to_be_bytes());
+    block[fields_at + 4..fields_at + 8].copy_from_slice(&400u32.to_be_bytes());
+    let path = write_temp("flac_picture_fields", "flac", &flac_file(&[
+        (6, flac_picture(0, "image/jpeg", BACK)),
+        (6, block),
+    ]));
+
+    let cover = SongMetadata::cover_art(&path).unwrap().unwrap();
+    assert_eq!((cover.kind, cover.mime.as_str(), cover.description.as_str()), (3, "image/png", "cover"));
+    assert_eq!(cover.dimensions, Some((600, 400)));
+    assert_eq!(cover.data, FRONT);
+    // zeroed width/height mean "not recorded"
+    assert_eq!(SongMetadata::cover_arts(&path).unwrap()[0].dimensions, None);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_flac_without_front_cover_uses_first_picture() {
+    let path = write_temp("flac_no_front", "flac", &flac_file(&[
+        (6, flac_picture(4, "image/jpeg", BACK)),
+        (6, flac_picture(0, "image/png", FRONT)),
+    ]));
+    assert_eq!(SongMetadata::cover_art(&path).unwrap().unwrap().data, BACK);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_m4a_cover_skips_invalid_data_atoms() {
+    // a text-typed data atom, then an implicit-type JPEG
+    let covr = [data_atom(1, b"not an image"), data_atom(0, BACK)].concat();
+    let path = write_temp("covr_implicit", "m4a", &m4a_file(&[atom(b"covr", &covr)]));
+
+    let cover = SongMetadata::cover_art(&path).unwrap().unwrap();
+    assert_eq!((cover.kind, cover.mime.as_str(), cover.data.as_slice()), (3, "image/jpeg", BACK));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_truncated_m4a_cover_does_not_panic() {
+    let bytes = m4a_file(&[atom(b"covr", &data_atom(14, FRONT))]);
+    for cut in [8, bytes.len() / 2, bytes.len() - 1] {
+        let path = write_temp("covr_truncated", "m4a", &bytes[..cut]);
+        assert_eq!(SongMetadata::cover_art(&path).unwrap(), None, "cut at {cut}");
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_m4a_cover_after_media_data() {
+    // `mdat` ahead of `moov` is skipped by its header
+    let mut bytes = atom(b"ftyp", b"M4A \0\0\0\0");
+    bytes.extend(atom(b"mdat", &vec![0u8; 1 << 20]));
+    bytes.extend(m4a_file(&[atom(b"covr", &data_atom(14, FRONT))]).split_off(16));
+    let path = write_temp("covr_after_mdat", "m4a", &bytes);
+
+    assert_eq!(SongMetadata::cover_art(&path).unwrap().unwrap().data, FRONT);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_covers_only_read_from_tagged_containers() {
+    // a `moov/.../covr` tree behind something other than `ftyp`
+    let mut m4a = m4a_file(&[atom(b"covr", &data_atom(14, FRONT))]);
+    m4a[4..8].copy_from_slice(b"free");
+    let ogg = ogg_vorbis_file(44_100, &["TITLE=Song"], 44_100);
+    let bare = [mp3_frames(4, false), m4a.clone()].concat();
+
+    for (name, bytes) in [("covr_free", &m4a), ("covr_ogg", &ogg), ("covr_bare", &bare)] {
+        let path = write_temp(name, "bin", bytes);
+        assert!(SongMetadata::cover_arts(&path).unwrap().is_empty(), "{name}");
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_unsynchronised_apic() {
+    // JPEG markers are exactly what unsynchronisation stuffs with 0x00
+    let image = b"\xFF\xD8\xFF\xE0 jpeg \xFF\x00 body \xFF\xD9";
+    let body = [&b"\0image/jpeg\0\x03\0"[..], image].concat();
+
+    // v2.3: the whole tag is unsynchronised
+    let v23 = id3v2_tag(3, 0x80, &unsynchronise(&id3_frame(b"APIC", &body)));
+    // v2.4: only this frame, with a data length indicator, and the tag flag clear
+    let mut frame = id3v24_frame(b"APIC", &[&synchsafe(body.len() as u32)[..], &unsynchronise(&body)].concat());
+    frame[9] = 0x03;
+    let v24 = id3v2_tag(4, 0, &frame);
+
+    for (name, bytes) in [("apic_unsync_v23", v23), ("apic_unsync_v24", v24)] {
+        let path = write_temp(name, "mp3", &bytes);
+        let cover = SongMetadata::cover_art(&path).unwrap().unwrap();
+        assert_eq!((cover.kind, cover.mime.as_str(), cover.data.as_slice()), (3, "image/jpeg", &image[..]), "{name}");
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_id3v2_tpos() {
+    let path = write_temp("tpos", "mp3", &mp3_file(&[id3_text(b"TPOS", "2/3")]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.disc_number, meta.disc_total), (Some(2), Some(3)));
+    fs::remove_file(path).unwrap();
+
+    let path = write_temp("tpos_single", "mp3", &mp3_file(&[id3_text(b"TPOS", "1")]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.disc_number, meta.disc_total), (Some(1), None));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_vorbis_disc_number_and_total() {
+    let split = write_temp("discnumber", "flac", &flac_file(&[(4, vorbis_comments(&["DISCNUMBER=2", "DISCTOTAL=3"]))]));
+    let pair = write_temp("discnumber", "ogg", &ogg_vorbis_file(44_100, &["TOTALDISCS=3", "DISCNUMBER=2/3"], 44_100));
+    for path in [split, pair] {
+        let meta = SongMetadata::from_file(&path).unwrap();
+        assert_eq!((meta.disc_number, meta.disc_total), (Some(2), Some(3)));
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_non_numeric_disc_is_none() {
+    let mp3 = write_temp("tpos_text", "mp3", &mp3_file(&[id3_text(b"TPOS", "Bonus")]));
+    let flac = write_temp("discnumber_text", "flac", &flac_file(&[(4, vorbis_comments(&["DISCNUMBER=A", "DISCTOTAL=two"]))]));
+    for path in [mp3, flac] {
+        let meta = SongMetadata::from_file(&path).unwrap();
+        assert_eq!((meta.disc_number, meta.disc_total), (None, None));
+        fs::remove_file(path).unwrap();
+    }
+}
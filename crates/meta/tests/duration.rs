@@ -0,0 +1,119 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+use std::io::Cursor;
+use std::time::Duration;
+
+#[test]
+fn test_set_duration_from_samples() {
+    let mut meta = SongMetadata::default();
+    meta.set_duration_from_samples(441_000, 44_100);
+    assert_eq!(meta.duration_ms, Some(10_000));
+    assert_eq!(meta.duration(), Some(Duration::from_secs(10)));
+    meta.set_duration_from_samples(1_000, 0);
+    assert_eq!(meta.duration_ms, None);
+    assert_eq!(meta.duration(), None);
+}
+
+#[test]
+fn test_compute_durations_from_readers() {
+    // 1 s of 16-bit stereo at 44.1 kHz
+    let wav = wav_file(&[], 176_400);
+    assert_eq!(SongMetadata::compute_wav_duration(&mut Cursor::new(wav)).unwrap(), 1_000);
+
+    let flac = flac_file(&[]);
+    assert_eq!(SongMetadata::compute_flac_duration(&mut Cursor::new(flac)).unwrap(), 10_000);
+
+    let mp3 = mp3_frames(100, false);
+    assert_eq!(
+        SongMetadata::compute_mp3_duration(&mut Cursor::new(mp3)).unwrap(),
+        100 * 1152 * 1000 / 44_100
+    );
+
+    let mut mvhd = [0u8; 100];
+    mvhd[12..16].copy_from_slice(&1_000u32.to_be_bytes());
+    mvhd[16..20].copy_from_slice(&42_500u32.to_be_bytes());
+    let m4a = [atom(b"ftyp", b"M4A \0\0\0\0"), atom(b"moov", &full_atom(b"mvhd", &mvhd[4..]))].concat();
+    assert_eq!(SongMetadata::compute_m4a_duration(&mut Cursor::new(m4a)).unwrap(), 42_500);
+
+    // 88_200 sample frames at 44.1 kHz
+    let aiff = aiff_file(&[aiff_chunk(b"SSND", &[0u8; 64]), aiff_comm(88_200)]);
+    assert_eq!(SongMetadata::compute_aiff_duration(&mut Cursor::new(aiff)).unwrap(), 2_000);
+
+    let vorbis = ogg_vorbis_file(44_100, &[], 441_000);
+    assert_eq!(SongMetadata::compute_ogg_duration(&mut Cursor::new(vorbis)).unwrap(), 10_000);
+    let opus = ogg_opus_file(312, &[], 312 + 96_000);
+    assert_eq!(SongMetadata::compute_ogg_duration(&mut Cursor::new(opus)).unwrap(), 2_000);
+}
+
+#[test]
+fn test_duration_of_matches_full_parse() {
+    let mut xing = id3v2_tag(3, 0, &id3_text(b"TIT2", "Song"));
+    xing.extend(xing_frame(b"Xing", 1_000));
+    xing.extend(mp3_frames(3, false));
+    let files = [
+        ("duration_of_xing", "mp3", xing),
+        ("duration_of_cbr", "mp3", mp3_frames(20, false)),
+        ("duration_of", "flac", flac_file(&[])),
+        ("duration_of", "wav", wav_file(&[], 176_400)),
+        ("duration_of", "ogg", ogg_vorbis_file(44_100, &[], 88_200)),
+        ("duration_of", "aiff", aiff_file(&[aiff_comm(44_100)])),
+    ];
+    for (name, ext, bytes) in files {
+        let path = write_temp(name, ext, &bytes);
+        let duration = SongMetadata::duration_of(&path).unwrap();
+        assert!(duration.is_some(), "{name}.{ext}");
+        assert_eq!(duration, SongMetadata::from_file(&path).unwrap().duration_ms, "{name}.{ext}");
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_duration_of_unknown_input() {
+    let path = write_temp("duration_of_junk", "bin", b"no audio in here at all");
+    assert_eq!(SongMetadata::duration_of(&path).unwrap(), None);
+    fs::remove_file(path).unwrap();
+    assert!(SongMetadata::duration_of("/nonexistent/song.mp3").is_err());
+}
+
+/// A `trak` with a `hdlr` of `handler` and a version 0 or 1 `mdhd`.
+fn m4a_track(handler: &[u8; 4], version: u8, timescale: u32, duration: u64) -> Vec<u8> {
+    let mut mdhd = vec![version, 0, 0, 0];
+    if version == 1 {
+        mdhd.extend_from_slice(&[0u8; 16]);
+        mdhd.extend_from_slice(&timescale.to_be_bytes());
+        mdhd.extend_from_slice(&duration.to_be_bytes());
+    } else {
+        mdhd.extend_from_slice(&[0u8; 8]);
+        mdhd.extend_from_slice(&timescale.to_be_bytes());
+        mdhd.extend_from_slice(&(duration as u32).to_be_bytes());
+    }
+    mdhd.extend_from_slice(&[0u8; 4]); // language, quality
+    let mut hdlr = vec![0u8; 4];
+    hdlr.extend_from_slice(handler);
+    hdlr.extend_from_slice(&[0u8; 13]);
+    atom(b"trak", &atom(b"mdia", &[atom(b"mdhd", &mdhd), full_atom(b"hdlr", &hdlr)].concat()))
+}
+
+#[test]
+fn test_m4a_duration_falls_back_to_audio_mdhd() {
+    // a zero movie duration, and a longer video track ahead of the audio
+    let mvhd = full_atom(b"mvhd", &[0u8; 96]);
+    for version in [0, 1] {
+        let moov = atom(b"moov", &[
+            mvhd.clone(),
+            m4a_track(b"vide", version, 600, 600 * 10),
+            m4a_track(b"soun", version, 44_100, 44_100 * 3 + 22_050),
+        ].concat());
+        let m4a = [atom(b"ftyp", b"M4A \0\0\0\0"), moov].concat();
+        assert_eq!(SongMetadata::compute_m4a_duration(&mut Cursor::new(m4a)).unwrap(), 3_500, "mdhd v{version}");
+    }
+
+    // without any mvhd at all
+    let moov = atom(b"moov", &m4a_track(b"soun", 0, 1_000, 2_000));
+    let m4a = [atom(b"ftyp", b"M4A \0\0\0\0"), moov].concat();
+    assert_eq!(SongMetadata::from_bytes(&m4a).unwrap().duration_ms, Some(2_000));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+// An empty title falls back to the file name, so "empty" shows up as that
+// fallback rather than as `Some("")`.
+fn assert_filename_title(meta: &SongMetadata) {
+    let title = meta.title.as_deref().unwrap();
+    assert!(title.starts_with("Meta Empty"), "unexpected title {title:?}");
+}
+
+#[test]
+fn test_zero_length_id3v2_frames_are_none() {
+    let path = write_temp("empty_id3", "mp3", &mp3_file(&[
+        id3_frame(b"TIT2", &[0]),
+        id3_text(b"TPE1", "  \0"),
+        id3_text(b"TALB", "Album"),
+    ]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_filename_title(&meta);
+    assert_eq!(meta.artist, None);
+    assert_eq!(meta.album.as_deref(), Some("Album"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_empty_vorbis_comments_are_none() {
+    let path = write_temp("empty_vorbis", "flac", &flac_file(&[
+        (4, vorbis_comments(&["TITLE=", "ARTIST= ", "ALBUM=Album"])),
+    ]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_filename_title(&meta);
+    assert_eq!(meta.artist, None);
+    assert_eq!(meta.album.as_deref(), Some("Album"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_empty_m4a_and_wav_text_are_none() {
+    let m4a = write_temp("empty_m4a", "m4a", &m4a_file(&[
+        atom(b"\xa9nam", &data_atom(1, b"")),
+        atom(b"\xa9ART", &data_atom(1, b"Artist")),
+    ]));
+    let meta = SongMetadata::from_file(&m4a).unwrap();
+    assert_filename_title(&meta);
+    assert_eq!(meta.artist.as_deref(), Some("Artist"));
+
+    let info = [b"INFO".to_vec(), riff_chunk(b"INAM", b"\0\0"), riff_chunk(b"IART", b"Artist\0")].concat();
+    let wav = write_temp("empty_wav", "wav", &wav_file(&[riff_chunk(b"LIST", &info)], 0));
+    let meta = SongMetadata::from_file(&wav).unwrap();
+    assert_filename_title(&meta);
+    assert_eq!(meta.artist.as_deref(), Some("Artist"));
+
+    fs::remove_file(m4a).unwrap();
+    fs::remove_file(wav).unwrap();
+}
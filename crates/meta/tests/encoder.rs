@@ -0,0 +1,27 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_encoder_from_every_format() {
+    let mp3 = write_temp("encoder", "mp3", &mp3_file(&[id3_text(b"TSSE", "LAME 3.100")]));
+    let flac = write_temp("encoder", "flac", &flac_file(&[(4, vorbis_comments(&["ENCODER=LAME 3.100"]))]));
+    let ogg = write_temp("encoder", "ogg", &ogg_vorbis_file(44_100, &["ENCODED_BY=LAME 3.100"], 44_100));
+    let m4a = write_temp("encoder", "m4a", &m4a_file(&[atom(b"\xa9too", &data_atom(1, b"LAME 3.100"))]));
+    for path in [mp3, flac, ogg, m4a] {
+        assert_eq!(SongMetadata::from_file(&path).unwrap().encoder.as_deref(), Some("LAME 3.100"));
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_encoder_absent() {
+    // a LAME tag in the audio frames is not taken as the encoder string
+    let bytes = [id3v2_tag(3, 0, &id3_text(b"TIT2", "Song")), xing_frame(b"Info", 10), mp3_frames(10, false)].concat();
+    let path = write_temp("no_encoder", "mp3", &bytes);
+    assert_eq!(SongMetadata::from_file(&path).unwrap().encoder, None);
+    fs::remove_file(path).unwrap();
+}
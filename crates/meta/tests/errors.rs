@@ -0,0 +1,54 @@
+mod common;
+
+use common::*;
+use meta::{MetaError, SongMetadata};
+
+use std::io::Cursor;
+
+#[test]
+fn test_missing_file_is_io_error() {
+    let err = SongMetadata::from_file("/nonexistent/song.mp3").unwrap_err();
+    assert!(matches!(err, MetaError::Io(ref e) if e.kind() == std::io::ErrorKind::NotFound));
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+fn test_unsupported_ogg_codec() {
+    let bytes = ogg_page(0x02, 0, &[b"\x80theora stream"], true);
+    assert!(matches!(SongMetadata::from_bytes(&bytes), Err(MetaError::UnsupportedFormat)));
+}
+
+#[test]
+fn test_duration_errors_name_the_problem() {
+    let mut streaminfo = flac_file(&[]);
+    // shrink STREAMINFO to 10 bytes
+    streaminfo[7] = 10;
+    assert!(matches!(
+        SongMetadata::compute_flac_duration(&mut Cursor::new(streaminfo)),
+        Err(MetaError::Truncated { expected: 18, got: 10 })
+    ));
+
+    // no `fmt ` chunk, so no byte rate
+    let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+    wav.extend(riff_chunk(b"data", &[0u8; 100]));
+    assert!(matches!(
+        SongMetadata::compute_wav_duration(&mut Cursor::new(wav)),
+        Err(MetaError::InvalidTag(_))
+    ));
+
+    let err = SongMetadata::compute_m4a_duration(&mut Cursor::new(m4a_file(&[]))).unwrap_err();
+    assert_eq!(err.to_string(), "missing or invalid mvhd");
+}
+
+#[test]
+fn test_truncated_id3v2_tag() {
+    let tag = id3v2_tag(3, 0, &[id3_text(b"TIT2", "Song"), id3_text(b"TPE1", "Artist")].concat());
+    let cut = &tag[..tag.len() - 5];
+    let declared = tag.len() - 10;
+    assert!(matches!(
+        SongMetadata::from_bytes(cut),
+        Err(MetaError::Truncated { expected, got }) if expected == declared && got == declared - 5
+    ));
+    // the intact tag still parses
+    assert_eq!(SongMetadata::from_bytes(&tag).unwrap().title.as_deref(), Some("Song"));
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::*;
+use meta::{AudioFormat, SongMetadata};
+
+use std::fs;
+
+fn detect(name: &str, ext: &str, bytes: &[u8]) -> AudioFormat {
+    let path = write_temp(name, ext, bytes);
+    let format = SongMetadata::from_file(&path).unwrap().format;
+    fs::remove_file(path).unwrap();
+    format
+}
+
+#[test]
+fn test_format_of_each_container() {
+    assert_eq!(detect("format", "mp3", &mp3_file(&[id3_text(b"TIT2", "Song")])), AudioFormat::Mp3);
+    assert_eq!(detect("format", "flac", &flac_file(&[])), AudioFormat::Flac);
+    assert_eq!(detect("format", "wav", &wav_file(&[wav_fmt(2, 44_100, 16)], 0)), AudioFormat::Wav);
+    assert_eq!(detect("format", "m4a", &m4a_file(&[])), AudioFormat::M4a);
+    assert_eq!(detect("format", "aiff", &aiff_file(&[aiff_comm(44_100)])), AudioFormat::Aiff);
+    assert_eq!(detect("format", "ogg", &ogg_vorbis_file(44_100, &[], 44_100)), AudioFormat::Vorbis);
+    assert_eq!(detect("format", "opus", &ogg_opus_file(312, &[], 48_312)), AudioFormat::Opus);
+}
+
+#[test]
+fn test_format_of_untagged_files() {
+    let mut id3v1 = mp3_frames(4, false);
+    id3v1.extend_from_slice(&id3v1_tag("Song", "Artist", "Album", "2001", 1, 17));
+    assert_eq!(detect("format_v1", "mp3", &id3v1), AudioFormat::Mp3);
+    assert_eq!(detect("format_bare", "mp3", &mp3_frames(4, false)), AudioFormat::Mp3);
+    assert_eq!(detect("format_junk", "bin", b"not an audio file at all"), AudioFormat::Unknown);
+}
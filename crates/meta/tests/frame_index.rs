@@ -0,0 +1,44 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_mp3_frame_index_offsets_and_samples() {
+    let tag = id3v2_tag(3, 0, &id3_text(b"TIT2", "Song"));
+    let bytes = [tag.clone(), mp3_frames(4, false)].concat();
+    let path = write_temp("frame_index", "mp3", &bytes);
+
+    let index = SongMetadata::mp3_frame_index(&path).unwrap();
+    let expected: Vec<(u64, u32)> = (0..4)
+        .map(|i| ((tag.len() + i * MP3_FRAME_LEN) as u64, i as u32 * 1152))
+        .collect();
+    assert_eq!(index, expected);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_mp3_frame_index_walks_past_xing_header() {
+    let xing = xing_frame(b"Xing", 3);
+    let bytes = [xing.clone(), mp3_frames(3, false)].concat();
+    let path = write_temp("frame_index_xing", "mp3", &bytes);
+
+    let index = SongMetadata::mp3_frame_index(&path).unwrap();
+    assert_eq!(index.len(), 3);
+    assert_eq!(index[0], (xing.len() as u64, 0));
+    assert_eq!(index[2].1, 2 * 1152);
+    // the duration path still stops at the header
+    assert_eq!(SongMetadata::duration_of(&path).unwrap(), Some(3 * 1152 * 1000 / 44_100));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_mp3_frame_index_without_frames() {
+    let path = write_temp("frame_index_empty", "mp3", b"not audio at all");
+    assert!(SongMetadata::mp3_frame_index(&path).unwrap().is_empty());
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::*;
+use meta::{AudioFormat, SongMetadata};
+
+use std::fs::{self, File};
+use std::io::{BufReader, Cursor};
+
+#[test]
+fn test_from_bytes_matches_from_file() {
+    let inputs = [
+        ("bytes", "mp3", mp3_file(&[id3_text(b"TIT2", "Song"), id3_text(b"TPE1", "Artist")])),
+        ("bytes", "flac", flac_file(&[(4, vorbis_comments(&["TITLE=Song", "ARTIST=Artist"]))])),
+        ("bytes", "m4a", m4a_file(&[atom(b"\xa9nam", &data_atom(1, b"Song")), atom(b"\xa9ART", &data_atom(1, b"Artist"))])),
+        ("bytes", "ogg", ogg_vorbis_file(44_100, &["TITLE=Song", "ARTIST=Artist"], 88_200)),
+    ];
+    for (name, ext, bytes) in inputs {
+        let path = write_temp(name, ext, &bytes);
+        let from_file = SongMetadata::from_file(&path).unwrap();
+        let from_bytes = SongMetadata::from_bytes(&bytes).unwrap();
+        assert_eq!(from_bytes.title.as_deref(), Some("Song"), "{ext}");
+        assert_eq!(from_bytes.artist, from_file.artist, "{ext}");
+        assert_eq!(from_bytes.format, from_file.format, "{ext}");
+        assert_eq!(from_bytes.duration_ms, from_file.duration_ms, "{ext}");
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_from_bytes_has_no_filename_title() {
+    let m = SongMetadata::from_bytes(&mp3_file(&[id3_text(b"TPE1", "Artist")])).unwrap();
+    assert_eq!(m.artist.as_deref(), Some("Artist"));
+    assert_eq!(m.title, None);
+
+    let empty = SongMetadata::from_bytes(b"short").unwrap();
+    assert_eq!((empty.title, empty.format), (None, AudioFormat::Unknown));
+}
+
+#[test]
+fn test_from_reader_over_buffered_and_owned_sources() {
+    let bytes = flac_file(&[(4, vorbis_comments(&["TITLE=Song"]))]);
+    let path = write_temp("reader", "flac", &bytes);
+
+    let mut buffered = BufReader::new(File::open(&path).unwrap());
+    let m = SongMetadata::from_reader(&mut buffered).unwrap();
+    assert_eq!((m.title.as_deref(), m.duration_ms, m.format), (Some("Song"), Some(10_000), AudioFormat::Flac));
+
+    let mut owned = Cursor::new(bytes);
+    let m = SongMetadata::from_reader(&mut owned).unwrap();
+    assert_eq!((m.title.as_deref(), m.duration_ms), (Some("Song"), Some(10_000)));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_from_reader_runs_whole_file_parsers() {
+    let bytes = ogg_opus_file(312, &["TITLE=Song"], 48_312);
+    let m = SongMetadata::from_reader(&mut Cursor::new(bytes)).unwrap();
+    assert_eq!((m.title.as_deref(), m.duration_ms, m.format), (Some("Song"), Some(1_000), AudioFormat::Opus));
+}
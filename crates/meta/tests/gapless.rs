@@ -0,0 +1,85 @@
+mod common;
+
+use common::*;
+use meta::{detect_album_gapless, AudioFormat, SongMetadata};
+
+use std::fs;
+use std::path::PathBuf;
+
+fn gapless_track(format: AudioFormat, delay: u16, padding: u16) -> SongMetadata {
+    SongMetadata {
+        format,
+        encoder_delay: Some(delay),
+        encoder_padding: Some(padding),
+        ..SongMetadata::default()
+    }
+}
+
+#[test]
+fn test_itunsmpb_is_read_from_m4a() {
+    let smpb = " 00000000 00000840 000001CA 00000000003F31F6 00000000 00000000";
+    let path = write_temp("itunsmpb", "m4a", &m4a_file(&[freeform("iTunSMPB", smpb)]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.encoder_delay, Some(2112));
+    assert_eq!(meta.encoder_padding, Some(458));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_detect_album_gapless() {
+    let album = vec![
+        (PathBuf::from("01.m4a"), gapless_track(AudioFormat::M4a, 2112, 458)),
+        // the codec comes from the parsed format, whatever the extension says
+        (PathBuf::from("02.mp4"), gapless_track(AudioFormat::M4a, 2112, 1000)),
+    ];
+    assert!(detect_album_gapless(&album));
+
+    let mut mixed_codec = album.clone();
+    mixed_codec.push((PathBuf::from("03.m4a"), gapless_track(AudioFormat::Mp3, 576, 1200)));
+    assert!(!detect_album_gapless(&mixed_codec));
+
+    let mut missing_info = album.clone();
+    missing_info.push((PathBuf::from("03.m4a"), SongMetadata { format: AudioFormat::M4a, ..SongMetadata::default() }));
+    assert!(!detect_album_gapless(&missing_info));
+
+    let mut mixed_rate = album.clone();
+    mixed_rate[1].1.sample_rate = Some(48_000);
+    assert!(!detect_album_gapless(&mixed_rate));
+
+    assert!(!detect_album_gapless(&[]));
+}
+
+/// An Info frame declaring `frames` frames, followed by a LAME tag.
+fn lame_frame(frames: u32, delay: u16, padding: u16) -> Vec<u8> {
+    let mut frame = xing_frame(b"Info", frames);
+    // only the frame-count field is flagged, so the LAME tag follows it
+    frame[48..57].copy_from_slice(b"LAME3.100");
+    let packed = (delay as u32) << 12 | padding as u32;
+    frame[69..72].copy_from_slice(&packed.to_be_bytes()[1..]);
+    frame
+}
+
+#[test]
+fn test_lame_tag_gapless_info() {
+    // 100 frames of 1152 samples at 44.1 kHz, minus 576 + 1_728 samples
+    let mut bytes = lame_frame(100, 576, 1_728);
+    bytes.extend(mp3_frames(3, false));
+    let path = write_temp("lame_gapless", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.encoder_delay, meta.encoder_padding), (Some(576), Some(1_728)));
+    assert_eq!(meta.duration_ms, Some((100 * 1152 - 576 - 1_728) * 1000 / 44_100));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_info_frame_without_lame_tag() {
+    let mut bytes = xing_frame(b"Info", 100);
+    bytes.extend(mp3_frames(3, false));
+    let path = write_temp("no_lame", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.encoder_delay, meta.encoder_padding), (None, None));
+    assert_eq!(meta.duration_ms, Some(100 * 1152 * 1000 / 44_100));
+    fs::remove_file(path).unwrap();
+}
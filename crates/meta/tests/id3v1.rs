@@ -0,0 +1,97 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+const LONG_TITLE: &str = "A Title Much Longer Than Thirty Characters, Really";
+const LONG_ARTIST: &str = "The Unreasonably Long Named Orchestra";
+
+#[test]
+fn test_enhanced_tag_extends_fields() {
+    let mut bytes = vec![0u8; 64];
+    bytes.extend(id3v1_enhanced_tag(LONG_TITLE, LONG_ARTIST, "Short Album", "Chamber Pop"));
+    bytes.extend(id3v1_tag(LONG_TITLE, LONG_ARTIST, "Short Album", "1999", 0, 13));
+    let path = write_temp("tag_plus", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.title.as_deref(), Some(LONG_TITLE));
+    assert_eq!(meta.artist.as_deref(), Some(LONG_ARTIST));
+    assert_eq!(meta.album.as_deref(), Some("Short Album"));
+    assert_eq!(meta.genre.as_deref(), Some("Chamber Pop"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_plain_tag_is_truncated_to_thirty() {
+    let mut bytes = vec![0u8; 400];
+    bytes.extend(id3v1_tag(LONG_TITLE, "Artist", "Album", "1999", 0, 13));
+    let path = write_temp("tag_plain", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.title.as_deref(), Some(&LONG_TITLE[..30]));
+    assert_eq!(meta.artist.as_deref(), Some("Artist"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_genre_code_maps_to_name() {
+    for (code, expected) in [(17, Some("Rock")), (0, Some("Blues")), (191, Some("Psybient")), (192, None), (255, None)] {
+        let mut bytes = vec![0u8; 64];
+        bytes.extend(id3v1_tag("Title", "Artist", "Album", "1999", 0, code));
+        let path = write_temp("v1_genre", "mp3", &bytes);
+        assert_eq!(SongMetadata::from_file(&path).unwrap().genre.as_deref(), expected, "code {code}");
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_id3v2_numeric_tcon() {
+    for (value, expected) in [("(17)", "Rock"), ("(52)", "Electronic"), ("Shoegaze", "Shoegaze"), ("(250)", "(250)")] {
+        let path = write_temp("tcon_code", "mp3", &mp3_file(&[id3_text(b"TCON", value)]));
+        assert_eq!(SongMetadata::from_file(&path).unwrap().genre.as_deref(), Some(expected), "TCON {value:?}");
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_comment_with_and_without_track_number() {
+    // ID3v1.1: 28-byte comment, a zero byte, then the track number
+    let mut v11 = id3v1_tag("Song", "Artist", "Album", "1999", 7, 13);
+    v11[97..105].copy_from_slice(b"ripped!!");
+    // ID3v1.0: the comment runs the full 30 bytes
+    let mut v10 = id3v1_tag("Song", "Artist", "Album", "1999", 0, 13);
+    v10[97..127].copy_from_slice(b"A thirty character comment ok.");
+
+    let with_track = write_temp("v1_comment_track", "mp3", &[vec![0u8; 64], v11].concat());
+    let meta = SongMetadata::from_file(&with_track).unwrap();
+    assert_eq!((meta.comment.as_deref(), meta.track_number), (Some("ripped!!"), Some(7)));
+
+    let full = write_temp("v1_comment_full", "mp3", &[vec![0u8; 64], v10].concat());
+    let meta = SongMetadata::from_file(&full).unwrap();
+    assert_eq!((meta.comment.as_deref(), meta.track_number), (Some("A thirty character comment ok."), None));
+
+    fs::remove_file(with_track).unwrap();
+    fs::remove_file(full).unwrap();
+}
+
+#[test]
+fn test_id3v2_tcon_references_with_refinements() {
+    let cases: [(&str, &[&str]); 7] = [
+        ("(17)Hardcore", &["Rock", "Hardcore"]),
+        ("(17)Rock", &["Rock"]),
+        ("(51)(39)", &["Techno-Industrial", "Noise"]),
+        ("(RX)", &["Remix"]),
+        ("(CR)(17)", &["Cover", "Rock"]),
+        ("((Parenthesised) Jazz", &["(Parenthesised) Jazz"]),
+        ("Post-Rock", &["Post-Rock"]),
+    ];
+    for (value, expected) in cases {
+        let path = write_temp("tcon_refine", "mp3", &mp3_file(&[id3_text(b"TCON", value)]));
+        let meta = SongMetadata::from_file(&path).unwrap();
+        assert_eq!(meta.genres, expected, "TCON {value:?}");
+        assert_eq!(meta.genre.as_deref(), Some(expected[0]));
+        fs::remove_file(path).unwrap();
+    }
+}
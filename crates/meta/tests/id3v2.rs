@@ -0,0 +1,189 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn latin1(text: &str) -> Vec<u8> {
+    [&[0u8][..], text.as_bytes()].concat()
+}
+
+fn read(name: &str, bytes: &[u8]) -> SongMetadata {
+    let path = write_temp(name, "mp3", bytes);
+    let meta = SongMetadata::from_file(&path).unwrap();
+    fs::remove_file(path).unwrap();
+    meta
+}
+
+#[test]
+fn test_id3v22_frames() {
+    let body = [
+        id3v22_frame(b"TT2", &latin1("Old Song")),
+        id3v22_frame(b"TP1", &latin1("Old Artist")),
+        id3v22_frame(b"TAL", &latin1("Old Album")),
+        id3v22_frame(b"TCO", &latin1("(17)")),
+        id3v22_frame(b"TRK", &latin1("2/9")),
+    ]
+    .concat();
+    let meta = read("v22", &id3v2_tag(2, 0, &body));
+    assert_eq!(meta.title.as_deref(), Some("Old Song"));
+    assert_eq!(meta.artist.as_deref(), Some("Old Artist"));
+    assert_eq!(meta.album.as_deref(), Some("Old Album"));
+    assert_eq!(meta.genre.as_deref(), Some("Rock"));
+    assert_eq!((meta.track_number, meta.track_total), (Some(2), Some(9)));
+}
+
+// "ÿa" as UTF-16BE is 00 FF 00 61, which unsynchronisation turns into
+// 00 FF 00 00 61
+const UTF16_TITLE: &[u8] = &[1, 0x00, 0xFF, 0x00, 0x61];
+
+#[test]
+fn test_unsynchronised_v23_tag() {
+    let frames = [id3_frame(b"TIT2", UTF16_TITLE), id3_text(b"TPE1", "Artist")].concat();
+    let meta = read("unsync_v23", &id3v2_tag(3, 0x80, &unsynchronise(&frames)));
+    assert_eq!(meta.title.as_deref(), Some("ÿa"));
+    assert_eq!(meta.artist.as_deref(), Some("Artist"));
+}
+
+#[test]
+fn test_unsynchronised_v24_frames() {
+    // in v2.4 each frame is unsynchronised on its own and sized accordingly
+    let body = unsynchronise(UTF16_TITLE);
+    let frames = [id3v24_frame(b"TIT2", &body), id3v24_frame(b"TPE1", &latin1("Artist"))].concat();
+    let meta = read("unsync_v24", &id3v2_tag(4, 0x80, &frames));
+    assert_eq!(meta.title.as_deref(), Some("ÿa"));
+    assert_eq!(meta.artist.as_deref(), Some("Artist"));
+}
+
+/// An ID3v2.4 frame with the given format flags (the second flag byte).
+fn id3v24_frame_with_format(id: &[u8; 4], format: u8, body: &[u8]) -> Vec<u8> {
+    let mut frame = id3v24_frame(id, body);
+    frame[9] = format;
+    frame
+}
+
+#[test]
+fn test_v24_frame_unsync_and_data_length_flags() {
+    // per-frame flags only: 0x02 unsynchronised, 0x01 a synchsafe data
+    // length indicator ahead of the body; the tag header flag is clear
+    let body = [&synchsafe(UTF16_TITLE.len() as u32)[..], &unsynchronise(UTF16_TITLE)].concat();
+    let frames = [
+        id3v24_frame_with_format(b"TIT2", 0x03, &body),
+        id3v24_frame_with_format(b"TALB", 0x01, &[&synchsafe(6)[..], &latin1("Album")].concat()),
+        id3v24_frame(b"TPE1", &latin1("Artist")),
+    ]
+    .concat();
+    let meta = read("frame_flags_v24", &id3v2_tag(4, 0, &frames));
+    assert_eq!(meta.title.as_deref(), Some("ÿa"));
+    assert_eq!(meta.album.as_deref(), Some("Album"));
+    assert_eq!(meta.artist.as_deref(), Some("Artist"));
+}
+
+#[test]
+fn test_v24_compressed_frame_is_skipped() {
+    let frames = [
+        id3v24_frame_with_format(b"TALB", 0x09, &[&synchsafe(6)[..], &[0x78, 0x9C, 0x01, 0x02]].concat()),
+        id3v24_frame(b"TPE1", &latin1("Artist")),
+    ]
+    .concat();
+    let meta = read("compressed_v24", &id3v2_tag(4, 0, &frames));
+    assert_eq!(meta.album, None);
+    assert_eq!(meta.artist.as_deref(), Some("Artist"));
+}
+
+#[test]
+fn test_extended_header_is_skipped() {
+    // v2.3: 4-byte size excluding itself, then flags and padding size
+    let v23_ext = [&6u32.to_be_bytes()[..], &[0, 0, 0, 0, 0, 0]].concat();
+    let body = [v23_ext, id3_text(b"TPE1", "Artist")].concat();
+    assert_eq!(read("ext_v23", &id3v2_tag(3, 0x40, &body)).artist.as_deref(), Some("Artist"));
+
+    // v2.4: synchsafe size covering the whole header
+    let v24_ext = [&synchsafe(6)[..], &[1, 0]].concat();
+    let body = [v24_ext, id3v24_frame(b"TPE1", &latin1("Artist"))].concat();
+    assert_eq!(read("ext_v24", &id3v2_tag(4, 0x40, &body)).artist.as_deref(), Some("Artist"));
+}
+
+#[test]
+fn test_oversized_extended_header() {
+    let body = [&0x7FFF_FFFFu32.to_be_bytes()[..], &id3_text(b"TPE1", "Artist")].concat();
+    let meta = read("ext_oversized", &id3v2_tag(3, 0x40, &body));
+    assert_eq!(meta.artist, None);
+}
+
+#[test]
+fn test_cover_after_extended_header() {
+    let v23_ext = [&6u32.to_be_bytes()[..], &[0, 0, 0, 0, 0, 0]].concat();
+    let body = [v23_ext, apic(3, "image/png", b"\x89PNG image")].concat();
+    let path = write_temp("ext_cover", "mp3", &id3v2_tag(3, 0x40, &body));
+    assert_eq!(SongMetadata::cover_art(&path).unwrap().unwrap().data, b"\x89PNG image");
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_utf8_and_utf16be_text_frames() {
+    let utf8 = [&[3u8][..], "Björk\0".as_bytes()].concat();
+    let utf16be: Vec<u8> = std::iter::once(2u8)
+        .chain("Сигур Рос".encode_utf16().flat_map(|u| u.to_be_bytes()))
+        .chain([0, 0])
+        .collect();
+    let frames = [id3v24_frame(b"TPE1", &utf8), id3v24_frame(b"TIT2", &utf16be)].concat();
+    let meta = read("utf8_frames", &id3v2_tag(4, 0, &frames));
+    assert_eq!(meta.artist.as_deref(), Some("Björk"));
+    assert_eq!(meta.title.as_deref(), Some("Сигур Рос"));
+}
+
+#[test]
+fn test_latin1_text_frames() {
+    // "Café Über" in ISO-8859-1: é = E9, Ü = DC, invalid as UTF-8
+    let title = [&[0u8][..], b"Caf\xE9 \xDCber"].concat();
+    let frames = [id3_frame(b"TIT2", &title), id3_text(b"TPE1", "Björk")].concat();
+    let meta = read("latin1_frames", &mp3_file(&[frames]));
+    assert_eq!(meta.title.as_deref(), Some("Café Über"));
+    // UTF-8 written under encoding 0 is still read as UTF-8
+    assert_eq!(meta.artist.as_deref(), Some("Björk"));
+}
+
+fn utf16_frame(text: &str, bom: &[u8], little_endian: bool) -> Vec<u8> {
+    let mut body = vec![1u8];
+    body.extend_from_slice(bom);
+    for unit in text.encode_utf16().chain([0]) {
+        body.extend(if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() });
+    }
+    body
+}
+
+#[test]
+fn test_utf16_byte_order_marks() {
+    let frames = [
+        id3_frame(b"TIT2", &utf16_frame("Café", &[0xFF, 0xFE], true)),
+        id3_frame(b"TPE1", &utf16_frame("Ørjan", &[0xFE, 0xFF], false)),
+        id3_frame(b"TALB", &utf16_frame("No BOM", &[], false)),
+    ]
+    .concat();
+    let meta = read("utf16_bom", &mp3_file(&[frames]));
+    assert_eq!(meta.title.as_deref(), Some("Café"));
+    assert_eq!(meta.artist.as_deref(), Some("Ørjan"));
+    assert_eq!(meta.album.as_deref(), Some("No BOM"));
+}
+
+#[test]
+fn test_multi_value_frames() {
+    let artists = [&[3u8][..], "Daft Punk\0Pharrell Williams\0Nile Rodgers".as_bytes()].concat();
+    let genres = utf16_frame("Disco\0(17)", &[0xFF, 0xFE], true);
+    let frames = [id3v24_frame(b"TPE1", &artists), id3v24_frame(b"TCON", &genres)].concat();
+    let meta = read("multi_value", &id3v2_tag(4, 0, &frames));
+
+    assert_eq!(meta.artists, ["Daft Punk", "Pharrell Williams", "Nile Rodgers"]);
+    assert_eq!(meta.artist.as_deref(), Some("Daft Punk"));
+    assert_eq!(meta.genres, ["Disco", "Rock"]);
+    assert_eq!(meta.genre.as_deref(), Some("Disco"));
+}
+
+#[test]
+fn test_single_value_frames_fill_lists() {
+    let meta = read("single_value", &mp3_file(&[id3_text(b"TPE1", "Artist"), id3_text(b"TCON", "(17)")]));
+    assert_eq!(meta.artists, ["Artist"]);
+    assert_eq!(meta.genres, ["Rock"]);
+}
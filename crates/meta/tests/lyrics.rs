@@ -0,0 +1,59 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+const LYRICS: &str = "First line\nSecond line";
+
+fn uslt(encoding: u8, descriptor: &[u8], lyrics: &[u8]) -> Vec<u8> {
+    let mut body = vec![encoding];
+    body.extend_from_slice(b"eng");
+    body.extend_from_slice(descriptor);
+    body.extend_from_slice(lyrics);
+    body
+}
+
+fn utf16le(s: &str) -> Vec<u8> {
+    let mut out = vec![0xFF, 0xFE];
+    out.extend(s.encode_utf16().flat_map(u16::to_le_bytes));
+    out
+}
+
+#[test]
+fn test_id3v2_uslt_utf8() {
+    let frame = uslt(3, b"verse\0", LYRICS.as_bytes());
+    let path = write_temp("uslt_utf8", "mp3", &mp3_file(&[id3_frame(b"USLT", &frame)]));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().lyrics.as_deref(), Some(LYRICS));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_id3v2_uslt_utf16_with_descriptor() {
+    let frame = uslt(1, &[utf16le("verse"), vec![0, 0]].concat(), &utf16le(LYRICS));
+    let path = write_temp("uslt_utf16", "mp3", &mp3_file(&[id3_frame(b"USLT", &frame)]));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().lyrics.as_deref(), Some(LYRICS));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_id3v2_uslt_without_descriptor_terminator() {
+    let frame = uslt(3, b"", b"no terminator");
+    let path = write_temp("uslt_bad", "mp3", &mp3_file(&[id3_frame(b"USLT", &frame)]));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().lyrics, None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_vorbis_and_m4a_lyrics() {
+    let lyrics = format!("LYRICS={LYRICS}");
+    let unsynced = format!("UNSYNCEDLYRICS={LYRICS}");
+    let flac = write_temp("lyrics", "flac", &flac_file(&[(4, vorbis_comments(&[&lyrics]))]));
+    let ogg = write_temp("lyrics", "ogg", &ogg_vorbis_file(44_100, &[&unsynced], 44_100));
+    let m4a = write_temp("lyrics", "m4a", &m4a_file(&[atom(b"\xa9lyr", &data_atom(1, LYRICS.as_bytes()))]));
+    for path in [flac, ogg, m4a] {
+        assert_eq!(SongMetadata::from_file(&path).unwrap().lyrics.as_deref(), Some(LYRICS));
+        fs::remove_file(path).unwrap();
+    }
+}
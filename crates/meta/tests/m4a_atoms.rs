@@ -0,0 +1,87 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+#[test]
+fn test_ilst_among_other_moov_children() {
+    // real files put `mvhd`, `trak` and a `hdlr` ahead of the tag items
+    let ilst = atom(b"ilst", &[
+        atom(b"\xa9nam", &data_atom(1, b"Title")),
+        atom(b"\xa9ART", &data_atom(1, b"Artist")),
+    ].concat());
+    let meta = full_atom(b"meta", &[full_atom(b"hdlr", b"\0\0\0\0mdirappl\0\0\0\0\0\0\0\0\0"), ilst].concat());
+    let moov = atom(b"moov", &[
+        full_atom(b"mvhd", &[0u8; 96]),
+        m4a_audio_track(2, 44_100, 128_000),
+        atom(b"udta", &meta),
+        atom(b"free", &[0u8; 16]),
+    ].concat());
+    let bytes = [atom(b"ftyp", b"M4A \0\0\0\0"), moov, atom(b"mdat", &[0u8; 64])].concat();
+
+    let meta = SongMetadata::from_bytes(&bytes).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Title"));
+    assert_eq!(meta.artist.as_deref(), Some("Artist"));
+    assert_eq!(meta.sample_rate, Some(44_100));
+}
+
+#[test]
+fn test_items_outside_ilst_are_ignored() {
+    let mut bytes = m4a_file(&[atom(b"\xa9nam", &data_atom(1, b"Tagged"))]);
+    bytes.extend(atom(b"\xa9ART", &data_atom(1, b"Stray")));
+    // a `udta` child that is not an item list
+    bytes.extend(atom(b"udta", &atom(b"\xa9alb", &data_atom(1, b"Stray"))));
+
+    let meta = SongMetadata::from_bytes(&bytes).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Tagged"));
+    assert_eq!((meta.artist, meta.album), (None, None));
+}
+
+#[test]
+fn test_meta_version_and_flags_are_skipped() {
+    let ilst = atom(b"ilst", &atom(b"\xa9nam", &data_atom(1, b"Title")));
+    // nonzero version/flags: read as an atom size they would swallow `ilst`
+    let mut meta_body = vec![0, 0, 0, 9];
+    meta_body.extend(ilst);
+    let moov = atom(b"moov", &atom(b"udta", &atom(b"meta", &meta_body)));
+    let bytes = [atom(b"ftyp", b"M4A \0\0\0\0"), moov].concat();
+
+    assert_eq!(SongMetadata::from_bytes(&bytes).unwrap().title.as_deref(), Some("Title"));
+}
+
+/// An atom in the 64-bit size form: a 32-bit size of 1, then the real size
+/// after the type.
+fn large_atom(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = 1u32.to_be_bytes().to_vec();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(&((body.len() + 16) as u64).to_be_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+#[test]
+fn test_64_bit_atom_sizes() {
+    // version 1 mvhd: 64-bit times, timescale at 20, 64-bit duration at 24
+    let mut mvhd = vec![1, 0, 0, 0];
+    mvhd.extend_from_slice(&[0u8; 16]);
+    mvhd.extend_from_slice(&1_000u32.to_be_bytes());
+    mvhd.extend_from_slice(&42_500u64.to_be_bytes());
+    mvhd.extend_from_slice(&[0u8; 80]);
+    let mut moov = atom(b"mvhd", &mvhd);
+    moov.extend(atom(b"udta", &full_atom(b"meta", &large_atom(b"ilst", &atom(b"\xa9nam", &data_atom(1, b"Mix"))))));
+
+    // the audio comes first, so the moov is only reached by skipping it
+    let bytes = [atom(b"ftyp", b"M4A \0\0\0\0"), large_atom(b"mdat", &[0u8; 256]), large_atom(b"moov", &moov)].concat();
+    let meta = SongMetadata::from_bytes(&bytes).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Mix"));
+    assert_eq!(meta.duration_ms, Some(42_500));
+}
+
+#[test]
+fn test_atom_size_zero_runs_to_end() {
+    let mut bytes = m4a_file(&[atom(b"\xa9nam", &data_atom(1, b"Title"))]);
+    bytes.extend_from_slice(&0u32.to_be_bytes());
+    bytes.extend_from_slice(b"mdat");
+    bytes.extend_from_slice(&[0xFF; 64]);
+    assert_eq!(SongMetadata::from_bytes(&bytes).unwrap().title.as_deref(), Some("Title"));
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_m4a_freeform_items_in_extra() {
+    let path = write_temp("freeform_extra", "m4a", &m4a_file(&[
+        atom(b"\xa9nam", &data_atom(1, b"Song")),
+        freeform("MusicBrainz Track Id", "b1a9c0e9-d987-4042-ae91-78d6a3267d69"),
+        freeform("CUSTOM_TAG", "custom value"),
+        freeform("replaygain_track_gain", "-6.48 dB"),
+    ]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Song"));
+    assert_eq!(meta.extra.len(), 3);
+    assert_eq!(meta.extra["MusicBrainz Track Id"], "b1a9c0e9-d987-4042-ae91-78d6a3267d69");
+    assert_eq!(meta.extra["CUSTOM_TAG"], "custom value");
+    // still read into its dedicated field as well
+    assert_eq!(meta.extra["replaygain_track_gain"], "-6.48 dB");
+    assert_eq!(meta.replay_gain_track_db, Some(-6.48));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_extra_is_empty_without_freeform_items() {
+    let path = write_temp("freeform_none", "m4a", &m4a_file(&[atom(b"\xa9nam", &data_atom(1, b"Song"))]));
+    assert!(SongMetadata::from_file(&path).unwrap().extra.is_empty());
+    fs::remove_file(path).unwrap();
+}
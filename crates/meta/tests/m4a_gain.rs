@@ -0,0 +1,31 @@
+mod common;
+
+use common::{freeform, m4a_file, write_temp};
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_m4a_replaygain_freeform() {
+    let path = write_temp("replaygain", "m4a", &m4a_file(&[
+        freeform("replaygain_track_gain", "-6.48 dB"),
+        freeform("REPLAYGAIN_ALBUM_GAIN", "-7.10 dB"),
+        freeform("iTunNORM", " 00002710 00002710 0 0 0 0 0 0 0 0"),
+    ]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.replay_gain_track_db, Some(-6.48));
+    assert_eq!(meta.replay_gain_album_db, Some(-7.10));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_m4a_sound_check_fallback() {
+    let path = write_temp("itunnorm", "m4a", &m4a_file(&[
+        freeform("iTunNORM", " 00002710 00001388 0 0 0 0 0 0 0 0"),
+    ]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    let gain = meta.replay_gain_track_db.unwrap();
+    assert!((gain + 10.0).abs() < 1e-4);
+    assert_eq!(meta.replay_gain_album_db, None);
+    fs::remove_file(path).unwrap();
+}
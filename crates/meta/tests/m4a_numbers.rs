@@ -0,0 +1,45 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn number_pair(number: u16, total: u16) -> Vec<u8> {
+    let mut value = vec![0, 0];
+    value.extend_from_slice(&number.to_be_bytes());
+    value.extend_from_slice(&total.to_be_bytes());
+    value.extend_from_slice(&[0, 0]);
+    data_atom(0, &value)
+}
+
+#[test]
+fn test_m4a_trkn_and_disk() {
+    let path = write_temp("trkn", "m4a", &m4a_file(&[
+        atom(b"trkn", &number_pair(3, 12)),
+        atom(b"disk", &number_pair(1, 2)),
+    ]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.track_number, meta.track_total), (Some(3), Some(12)));
+    assert_eq!((meta.disc_number, meta.disc_total), (Some(1), Some(2)));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_m4a_trkn_without_total() {
+    let path = write_temp("trkn_no_total", "m4a", &m4a_file(&[atom(b"trkn", &number_pair(7, 0))]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.track_number, meta.track_total), (Some(7), None));
+    assert_eq!(meta.disc_number, None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_m4a_disk_without_trailing_padding() {
+    // iTunes writes `disk` with a 6-byte payload, two bytes shorter than `trkn`
+    let value = [0, 0, 0, 2, 0, 3];
+    let path = write_temp("disk_short", "m4a", &m4a_file(&[atom(b"disk", &data_atom(0, &value))]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.disc_number, meta.disc_total), (Some(2), Some(3)));
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_crc_protected_frames() {
+    let mut bytes = mp3_file(&[id3_text(b"TIT2", "Protected")]);
+    bytes.extend(mp3_frames(10, true));
+    let path = write_temp("crc_ok", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.crc_protected, Some(true));
+    assert_eq!(SongMetadata::verify_mp3_crc(&path).unwrap(), Some(true));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_crc_mismatch_is_reported() {
+    let mut bytes = mp3_file(&[id3_text(b"TIT2", "Rotten")]);
+    let audio_start = bytes.len();
+    bytes.extend(mp3_frames(10, true));
+    // flip a bit in the first frame's side information
+    bytes[audio_start + 10] ^= 0x04;
+    let path = write_temp("crc_bad", "mp3", &bytes);
+
+    assert_eq!(SongMetadata::verify_mp3_crc(&path).unwrap(), Some(false));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_unprotected_frames() {
+    let mut bytes = mp3_file(&[id3_text(b"TIT2", "Plain")]);
+    bytes.extend(mp3_frames(10, false));
+    let path = write_temp("crc_none", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.crc_protected, Some(false));
+    assert_eq!(meta.duration_ms, Some(10 * 1152 * 1000 / 44_100));
+    assert_eq!(SongMetadata::verify_mp3_crc(&path).unwrap(), None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_xing_frame_count_gives_duration() {
+    // the header claims far more frames than are present, so a frame walk
+    // would disagree with it
+    for tag in [b"Xing", b"Info"] {
+        let mut bytes = mp3_file(&[id3_text(b"TIT2", "VBR")]);
+        bytes.extend(xing_frame(tag, 1_000));
+        bytes.extend(mp3_frames(5, false));
+        let path = write_temp("xing", "mp3", &bytes);
+
+        let meta = SongMetadata::from_file(&path).unwrap();
+        assert_eq!(meta.duration_ms, Some(1_000 * 1152 * 1000 / 44_100));
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_xing_without_frame_count_falls_back_to_scan() {
+    let mut xing = xing_frame(b"Xing", 1_000);
+    xing[40..44].copy_from_slice(&0u32.to_be_bytes());
+    let mut bytes = mp3_file(&[id3_text(b"TIT2", "VBR")]);
+    bytes.extend(xing);
+    bytes.extend(mp3_frames(99, false));
+    let path = write_temp("xing_no_frames", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.duration_ms, Some(100 * 1152 * 1000 / 44_100));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_vbri_frame_count_gives_duration() {
+    let mut bytes = vbri_frame(2_000);
+    bytes.extend(mp3_frames(5, false));
+    let path = write_temp("vbri", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.duration_ms, Some(2_000 * 1152 * 1000 / 44_100));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_free_format_frames() {
+    // bitrate index 0; 1254-byte frames at 44.1 kHz are 384 kbps
+    let mut frame = vec![0u8; 1254];
+    frame[0..4].copy_from_slice(&[0xFF, 0xFB, 0x00, 0x00]);
+    let mut padded = frame.clone();
+    padded[2] |= 0x02;
+    padded.push(0);
+    let frames = [frame.repeat(10), padded, frame.repeat(9)].concat();
+    let bytes = [mp3_file(&[id3_text(b"TIT2", "Free")]), frames].concat();
+
+    let meta = SongMetadata::from_bytes(&bytes).unwrap();
+    assert_eq!(meta.duration_ms, Some(20 * 1152 * 1000 / 44_100));
+    assert_eq!(meta.bitrate_kbps, Some(384));
+
+    let path = write_temp("free_format", "mp3", &bytes);
+    assert_eq!(SongMetadata::mp3_frame_index(&path).unwrap().len(), 20);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_false_sync_in_junk_is_not_counted() {
+    // a lone frame header in trailing junk, not followed by another frame
+    let mut junk = vec![0u8; 10];
+    junk.extend_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+    junk.extend_from_slice(&[0x55; 600]);
+    let bytes = [mp3_file(&[id3_text(b"TIT2", "Junk")]), mp3_frames(10, false), junk.clone()].concat();
+    assert_eq!(SongMetadata::from_bytes(&bytes).unwrap().duration_ms, Some(10 * 1152 * 1000 / 44_100));
+
+    // and the same junk ahead of the audio
+    let bytes = [mp3_file(&[id3_text(b"TIT2", "Junk")]), junk, mp3_frames(10, false)].concat();
+    assert_eq!(SongMetadata::from_bytes(&bytes).unwrap().duration_ms, Some(10 * 1152 * 1000 / 44_100));
+}
+
+#[test]
+fn test_scan_of_file_larger_than_16_mib() {
+    let frames = 17 * 1024 * 1024 / MP3_FRAME_LEN + 1;
+    let bytes = [mp3_file(&[id3_text(b"TIT2", "Long")]), mp3_frames(frames, false)].concat();
+    let path = write_temp("large_scan", "mp3", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.duration_ms, Some((frames as u64 * 1152 * 1000) / 44_100));
+    assert_eq!(SongMetadata::mp3_frame_index(&path).unwrap().len(), frames);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_false_sync_before_tag_bytes_in_cover_art() {
+    // a fake frame header whose "next frame" would be the bytes `TAG`, deep
+    // inside an APIC image of a tag appended after the audio
+    let mut image = vec![0xFF, 0xFB, 0x90, 0x00];
+    image.resize(MP3_FRAME_LEN, 0x11);
+    image.extend_from_slice(b"TAG");
+    image.resize(4_000, 0x22);
+    let appended = id3v2_tag(3, 0, &apic(3, "image/jpeg", &image));
+    let bytes = [mp3_file(&[id3_text(b"TIT2", "Art")]), mp3_frames(10, false), appended].concat();
+
+    assert_eq!(SongMetadata::from_bytes(&bytes).unwrap().duration_ms, Some(10 * 1152 * 1000 / 44_100));
+}
+
+#[test]
+fn test_lone_frame_needs_a_tag_at_the_end() {
+    let tag = mp3_file(&[id3_text(b"TIT2", "Single")]);
+    let id3v1 = id3v1_tag("Single", "", "", "", 0, 0);
+    let one_frame = 1152 * 1000 / 44_100;
+
+    // an ID3v1 tag sits exactly 128 bytes before the end
+    let bytes = [tag.clone(), mp3_frames(1, false), id3v1.clone()].concat();
+    assert_eq!(SongMetadata::from_bytes(&bytes).unwrap().duration_ms, Some(one_frame));
+
+    // the same bytes anywhere else are not a tag
+    let bytes = [tag, mp3_frames(1, false), id3v1, vec![0x33; 1_000]].concat();
+    assert_ne!(SongMetadata::from_bytes(&bytes).unwrap().duration_ms, Some(one_frame));
+}
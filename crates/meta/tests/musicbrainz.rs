@@ -0,0 +1,62 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+const TRACK_ID: &str = "b1a9c0e9-d987-4042-ae91-78d6a3267d69";
+const ALBUM_ID: &str = "f5093c06-23e3-404f-aeaa-40f72885ee3a";
+
+fn txxx(description: &str, value: &str) -> Vec<u8> {
+    id3_frame(b"TXXX", format!("\x03{description}\0{value}").as_bytes())
+}
+
+fn ufid(owner: &str, id: &str) -> Vec<u8> {
+    id3_frame(b"UFID", format!("{owner}\0{id}").as_bytes())
+}
+
+fn assert_ids(path: &std::path::Path) {
+    let meta = SongMetadata::from_file(path).unwrap();
+    assert_eq!(meta.musicbrainz_track_id.as_deref(), Some(TRACK_ID));
+    assert_eq!(meta.musicbrainz_album_id.as_deref(), Some(ALBUM_ID));
+}
+
+#[test]
+fn test_musicbrainz_ids_from_vorbis_and_m4a() {
+    let track = format!("MUSICBRAINZ_TRACKID={TRACK_ID}");
+    let album = format!("MUSICBRAINZ_ALBUMID={ALBUM_ID}");
+    let flac = write_temp("mbid", "flac", &flac_file(&[(4, vorbis_comments(&[&track, &album]))]));
+    let m4a = write_temp("mbid", "m4a", &m4a_file(&[
+        freeform("MusicBrainz Track Id", TRACK_ID),
+        freeform("MusicBrainz Album Id", ALBUM_ID),
+    ]));
+    for path in [flac, m4a] {
+        assert_ids(&path);
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_musicbrainz_ids_from_id3v2() {
+    let path = write_temp("mbid_id3", "mp3", &mp3_file(&[
+        ufid("http://www.id3.org/dummy/ufid.html", "other-id"),
+        txxx("MusicBrainz Track Id", "stale-txxx-id"),
+        ufid("http://musicbrainz.org", TRACK_ID),
+        txxx("MusicBrainz Album Id", ALBUM_ID),
+    ]));
+    assert_ids(&path);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_musicbrainz_id_length_is_checked_loosely() {
+    let short = write_temp("mbid_short", "mp3", &mp3_file(&[txxx("MusicBrainz Album Id", "12345")]));
+    assert_eq!(SongMetadata::from_file(&short).unwrap().musicbrainz_album_id.as_deref(), Some("12345"));
+    fs::remove_file(short).unwrap();
+
+    let long = "x".repeat(65);
+    let path = write_temp("mbid_long", "mp3", &mp3_file(&[txxx("MusicBrainz Album Id", &long)]));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().musicbrainz_album_id, None);
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+#[test]
+fn test_ogg_vorbis_comments_and_duration() {
+    let bytes = ogg_vorbis_file(44_100, &["TITLE=Ogg Song", "ARTIST=Ogg Artist", "TRACKNUMBER=2"], 441_000);
+    let path = write_temp("vorbis", "ogg", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Ogg Song"));
+    assert_eq!(meta.artist.as_deref(), Some("Ogg Artist"));
+    assert_eq!(meta.track_number, Some(2));
+    assert_eq!(meta.duration_ms, Some(10_000));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_comment_packet_spanning_pages() {
+    let long_title = format!("TITLE={}", "x".repeat(700));
+    let mut packet = [&b"\x03vorbis"[..], &vorbis_comments(&[&long_title, "ARTIST=Spanning"]), &[1]].concat();
+    // pad so the first page can end exactly on a 255-byte boundary
+    let split = 510;
+    packet.resize(packet.len().max(split + 1), 0);
+
+    let mut bytes = ogg_page(0x02, 0, &[&vorbis_id_header(48_000)], true);
+    bytes.extend(ogg_page(0, u64::MAX, &[&packet[..split]], false));
+    bytes.extend(ogg_page(0x01, 0, &[&packet[split..]], true));
+    bytes.extend(ogg_page(0, 96_000, &[&[0u8; 10]], true));
+    // a trailing page with no finished packet carries granule -1
+    bytes.extend(ogg_page(0x04, u64::MAX, &[&[0u8; 255]], false));
+    let path = write_temp("vorbis_span", "ogg", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.title.as_deref().map(str::len), Some(700));
+    assert_eq!(meta.artist.as_deref(), Some("Spanning"));
+    assert_eq!(meta.duration_ms, Some(2_000));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_opus_tags_and_duration() {
+    // 3 s of audio after 312 samples of pre-skip
+    let bytes = ogg_opus_file(312, &["TITLE=Episode 12", "ARTIST=Podcast Host"], 312 + 3 * 48_000);
+    let path = write_temp("opus", "opus", &bytes);
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Episode 12"));
+    assert_eq!(meta.artist.as_deref(), Some("Podcast Host"));
+    assert_eq!(meta.duration_ms, Some(3_000));
+    fs::remove_file(path).unwrap();
+}
+
+/// Counts the bytes read through it.
+struct Counting<R> {
+    inner: R,
+    read: u64,
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for Counting<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn test_comments_are_read_without_loading_the_audio() {
+    let mut bytes = ogg_vorbis_file(44_100, &["TITLE=Streamed"], 0);
+    for page in 1..=40 {
+        bytes.extend(ogg_page(0, page * 44_100, &[&vec![0u8; 60_000]], true));
+    }
+    let mut reader = Counting { inner: Cursor::new(&bytes), read: 0 };
+
+    let meta = SongMetadata::from_reader(&mut reader).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Streamed"));
+    assert_eq!(meta.duration_ms, Some(40_000));
+    // the header pages, plus the tail scanned for the last granule
+    assert!(reader.read < 200_000, "read {} of {} bytes", reader.read, bytes.len());
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::*;
+use meta::{AudioFormat, SongMetadata};
+
+use std::fs;
+
+#[test]
+fn test_reader_defaults_match_from_file() {
+    let path = write_temp("reader_default", "mp3", &[
+        mp3_file(&[id3_text(b"TIT2", "Song"), id3_text(b"TPE1", "Artist")]),
+        mp3_frames(10, false),
+    ].concat());
+
+    let meta = SongMetadata::reader().from_file(&path).unwrap();
+    let plain = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.title, meta.duration_ms), (plain.title, plain.duration_ms));
+    assert!(meta.duration_ms.is_some());
+    assert_eq!(meta.cover, None);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_reader_without_duration() {
+    let mp3 = write_temp("reader_no_duration", "mp3", &[
+        mp3_file(&[id3_text(b"TIT2", "Song")]),
+        mp3_frames(10, false),
+    ].concat());
+    let flac = write_temp("reader_no_duration", "flac", &flac_file(&[(4, vorbis_comments(&["TITLE=Song"]))]));
+
+    for path in [&mp3, &flac] {
+        let meta = SongMetadata::reader().duration(false).from_file(path).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Song"));
+        assert_eq!(meta.duration_ms, None);
+    }
+    let mp3_meta = SongMetadata::reader().duration(false).from_file(&mp3).unwrap();
+    assert_eq!((mp3_meta.format, mp3_meta.sample_rate), (AudioFormat::Mp3, None));
+
+    fs::remove_file(mp3).unwrap();
+    fs::remove_file(flac).unwrap();
+}
+
+#[test]
+fn test_reader_without_tags_keeps_stream_properties() {
+    let path = write_temp("reader_no_tags", "mp3", &[
+        mp3_file(&[id3_text(b"TIT2", "Song"), id3_text(b"TPE1", "Artist")]),
+        mp3_frames(10, false),
+    ].concat());
+
+    let meta = SongMetadata::reader().tags(false).from_file(&path).unwrap();
+    assert_eq!((meta.title, meta.artist), (None, None));
+    assert_eq!(meta.format, AudioFormat::Mp3);
+    assert!(meta.duration_ms.is_some());
+    assert!(meta.sample_rate.is_some());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_reader_with_cover() {
+    let image = b"\x89PNG front cover bytes";
+    let mut body = b"\0image/png\0\x03\0".to_vec();
+    body.extend_from_slice(image);
+    let bytes = mp3_file(&[id3_text(b"TIT2", "Song"), id3_frame(b"APIC", &body)]);
+    let path = write_temp("reader_cover", "mp3", &bytes);
+
+    assert_eq!(SongMetadata::from_file(&path).unwrap().cover, None);
+    let meta = SongMetadata::reader().cover(true).from_bytes(&bytes).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Song"));
+    assert_eq!(meta.cover.unwrap().data, image);
+    assert_eq!(SongMetadata::reader().cover(true).max_cover_bytes(4).from_file(&path).unwrap().cover, None);
+
+    fs::remove_file(path).unwrap();
+}
+
+/// One tagged file ("Song") per parser branch.
+fn tagged_files() -> Vec<(&'static str, Vec<u8>)> {
+    let mut mvhd = [0u8; 96];
+    mvhd[8..12].copy_from_slice(&1_000u32.to_be_bytes());
+    mvhd[12..16].copy_from_slice(&1_000u32.to_be_bytes());
+    let mut m4a = m4a_file(&[atom(b"\xa9nam", &data_atom(1, b"Song"))]);
+    m4a.extend(atom(b"moov", &[full_atom(b"mvhd", &mvhd), m4a_audio_track(2, 44_100, 128_000)].concat()));
+
+    vec![
+        ("wav", wav_file(&[riff_chunk(b"id3 ", &id3v2_tag(3, 0, &id3_text(b"TIT2", "Song")))], 176_400)),
+        ("aiff", aiff_file(&[aiff_comm(44_100), aiff_chunk(b"NAME", b"Song")])),
+        ("vorbis", ogg_vorbis_file(44_100, &["TITLE=Song"], 44_100)),
+        ("opus", ogg_opus_file(0, &["TITLE=Song"], 48_000)),
+        ("m4a", m4a),
+        ("id3v1", [mp3_frames(10, false), id3v1_tag("Song", "", "", "", 0, 255)].concat()),
+    ]
+}
+
+#[test]
+fn test_reader_without_tags_in_every_format() {
+    for (name, bytes) in tagged_files() {
+        let full = SongMetadata::from_bytes(&bytes).unwrap();
+        assert_eq!(full.title.as_deref(), Some("Song"), "{name}");
+
+        let meta = SongMetadata::reader().tags(false).from_bytes(&bytes).unwrap();
+        assert_eq!(meta.title, None, "{name}");
+        assert_eq!(meta.format, full.format, "{name}");
+        assert_eq!(meta.sample_rate, full.sample_rate, "{name}");
+        assert!(meta.duration_ms.is_some(), "{name}");
+        assert_eq!(meta.duration_ms, full.duration_ms, "{name}");
+    }
+}
+
+#[test]
+fn test_reader_without_duration_in_every_format() {
+    for (name, bytes) in tagged_files() {
+        let meta = SongMetadata::reader().duration(false).from_bytes(&bytes).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Song"), "{name}");
+        assert_eq!(meta.duration_ms, None, "{name}");
+    }
+}
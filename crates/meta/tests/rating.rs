@@ -0,0 +1,36 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn popm(email: &str, rating: u8, play_count: u32) -> Vec<u8> {
+    let mut body = email.as_bytes().to_vec();
+    body.push(0);
+    body.push(rating);
+    body.extend_from_slice(&play_count.to_be_bytes());
+    id3_frame(b"POPM", &body)
+}
+
+#[test]
+fn test_id3v2_popm_rating() {
+    let path = write_temp("popm", "mp3", &mp3_file(&[popm("user@example.com", 196, 42)]));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().rating, Some(196));
+    fs::remove_file(path).unwrap();
+
+    let path = write_temp("popm_none", "mp3", &mp3_file(&[id3_text(b"TIT2", "Song")]));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().rating, None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_m4a_rating_atoms() {
+    let rtng = write_temp("rtng", "m4a", &m4a_file(&[atom(b"rtng", &data_atom(21, &[128]))]));
+    assert_eq!(SongMetadata::from_file(&rtng).unwrap().rating, Some(128));
+    fs::remove_file(rtng).unwrap();
+
+    let freeform = write_temp("rating_freeform", "m4a", &m4a_file(&[freeform("rating", "255")]));
+    assert_eq!(SongMetadata::from_file(&freeform).unwrap().rating, Some(255));
+    fs::remove_file(freeform).unwrap();
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn txxx(description: &str, value: &str) -> Vec<u8> {
+    let mut body = vec![3u8];
+    body.extend_from_slice(description.as_bytes());
+    body.push(0);
+    body.extend_from_slice(value.as_bytes());
+    id3_frame(b"TXXX", &body)
+}
+
+#[test]
+fn test_vorbis_comment_replaygain() {
+    let comments = ["REPLAYGAIN_TRACK_GAIN=-6.48 dB", "replaygain_album_gain=+1.25 dB"];
+    let flac = write_temp("rg_vorbis", "flac", &flac_file(&[(4, vorbis_comments(&comments))]));
+    let ogg = write_temp("rg_vorbis", "ogg", &ogg_vorbis_file(44_100, &comments, 44_100));
+    for path in [flac, ogg] {
+        let meta = SongMetadata::from_file(&path).unwrap();
+        assert_eq!((meta.replay_gain_track_db, meta.replay_gain_album_db), (Some(-6.48), Some(1.25)));
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_id3v2_txxx_replaygain() {
+    let path = write_temp("rg_txxx", "mp3", &mp3_file(&[
+        txxx("REPLAYGAIN_TRACK_GAIN", "-3.20 dB"),
+        txxx("replaygain_album_gain", "-4.00 dB"),
+        txxx("SOMETHING_ELSE", "-9.00 dB"),
+    ]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.replay_gain_track_db, meta.replay_gain_album_db), (Some(-3.2), Some(-4.0)));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_unparseable_gain_is_none() {
+    let path = write_temp("rg_bad", "mp3", &mp3_file(&[txxx("REPLAYGAIN_TRACK_GAIN", "loud")]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(meta.replay_gain_track_db, None);
+    fs::remove_file(path).unwrap();
+}
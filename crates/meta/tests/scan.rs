@@ -0,0 +1,40 @@
+mod common;
+
+use common::*;
+use meta::{MetaError, scan_all};
+
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_scan_all_keeps_input_order() {
+    let mut paths: Vec<PathBuf> = (0..12)
+        .map(|i| write_temp(&format!("scan_{i}"), "mp3", &mp3_file(&[id3_text(b"TIT2", &format!("Song {i}"))])))
+        .collect();
+    paths.insert(5, PathBuf::from("/nonexistent/scan.mp3"));
+
+    for threads in [0, 1, 4, 64] {
+        let results = scan_all(&paths, threads);
+        assert_eq!(results.len(), paths.len());
+        for (i, (path, result)) in results.iter().enumerate() {
+            assert_eq!(path, &paths[i]);
+            match i {
+                5 => assert!(matches!(result, Err(MetaError::Io(_)))),
+                _ => {
+                    let n = if i < 5 { i } else { i - 1 };
+                    assert_eq!(result.as_ref().unwrap().title, Some(format!("Song {n}")));
+                }
+            }
+        }
+    }
+
+    paths.remove(5);
+    for path in paths {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_scan_all_without_paths() {
+    assert!(scan_all(&[], 4).is_empty());
+}
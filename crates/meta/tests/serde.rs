@@ -0,0 +1,41 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::*;
+use meta::{AudioFormat, CoverArt, SongMetadata};
+
+use std::fs;
+
+#[test]
+fn test_metadata_json_round_trip() {
+    let path = write_temp("serde", "mp3", &mp3_file(&[
+        id3_text(b"TIT2", "Say \"Hello\"\\Goodbye"),
+        id3_text(b"TPE1", "Artist"),
+    ]));
+    let m = SongMetadata::from_file(&path).unwrap();
+
+    let json = serde_json::to_value(&m).unwrap();
+    assert_eq!(json["title"], "Say \"Hello\"\\Goodbye");
+    assert_eq!(json["format"], "Mp3");
+    // unset fields are left out rather than written as null
+    assert!(json.get("album").is_none());
+
+    let back: SongMetadata = serde_json::from_value(json).unwrap();
+    assert_eq!((back.title, back.artist, back.format), (m.title, m.artist, AudioFormat::Mp3));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cover_bytes_are_base64() {
+    let cover = CoverArt {
+        mime: "image/png".into(),
+        kind: 3,
+        description: String::new(),
+        dimensions: None,
+        data: b"\x89PNG".to_vec(),
+    };
+    let json = serde_json::to_value(&cover).unwrap();
+    assert_eq!(json["data"], "iVBORw==");
+    assert_eq!(serde_json::from_value::<CoverArt>(json).unwrap(), cover);
+}
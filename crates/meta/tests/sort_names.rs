@@ -0,0 +1,44 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_sort_names_from_every_format() {
+    let mp3 = write_temp("sort_names", "mp3", &mp3_file(&[
+        id3_text(b"TPE1", "The Beatles"),
+        id3_text(b"TSOT", "Hard Day's Night, A"),
+        id3_text(b"TSOP", "Beatles, The"),
+        id3_text(b"TSOA", "Hard Day's Night, A"),
+    ]));
+    let flac = write_temp("sort_names", "flac", &flac_file(&[(4, vorbis_comments(&[
+        "ARTIST=The Beatles",
+        "TITLESORT=Hard Day's Night, A",
+        "ARTISTSORT=Beatles, The",
+        "ALBUMSORT=Hard Day's Night, A",
+    ]))]));
+    let m4a = write_temp("sort_names", "m4a", &m4a_file(&[
+        atom(b"\xa9ART", &data_atom(1, b"The Beatles")),
+        atom(b"sonm", &data_atom(1, b"Hard Day's Night, A")),
+        atom(b"soar", &data_atom(1, b"Beatles, The")),
+        atom(b"soal", &data_atom(1, b"Hard Day's Night, A")),
+    ]));
+    for path in [mp3, flac, m4a] {
+        let meta = SongMetadata::from_file(&path).unwrap();
+        assert_eq!(meta.artist.as_deref(), Some("The Beatles"));
+        assert_eq!(meta.artist_sort.as_deref(), Some("Beatles, The"));
+        assert_eq!(meta.title_sort.as_deref(), Some("Hard Day's Night, A"));
+        assert_eq!(meta.album_sort.as_deref(), Some("Hard Day's Night, A"));
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_sort_names_absent() {
+    let path = write_temp("no_sort_names", "mp3", &mp3_file(&[id3_text(b"TPE1", "The Beatles")]));
+    let meta = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((meta.title_sort, meta.artist_sort, meta.album_sort), (None, None, None));
+    fs::remove_file(path).unwrap();
+}
@@ -1,34 +1,26 @@
-use utils::collect_music_files;
+use utils::{collect_music_files, detect_script, romanize, Script};
 use meta::{SongMetadata};
 const FILE_PATH: &str = "tests/output/test_reading_metadata.json"; 
 #[test]
 fn test_reading_metadata() {
-    fn is_roman_alphabet(s: &str) -> bool {
-        s.chars().all(|c| {
-            c.is_ascii_alphabetic() ||
-            c.is_ascii_digit() ||
-            c.is_ascii_whitespace() ||
-            c.is_ascii_punctuation()
-        })
+    /// Shows non-Latin text romanized, falling back only when that fails.
+    fn readable(s: &Option<String>, fallback: &str) -> String {
+        match s {
+            Some(n) if matches!(detect_script(n), Script::Latin | Script::Empty) => n.clone(),
+            Some(n) if matches!(detect_script(&romanize(n)), Script::Latin | Script::Empty) => romanize(n),
+            _ => fallback.to_string(),
+        }
     }
-    fn write_to_file(entries: Vec<String>) {
-        use std::fs::File;
-        use std::io::{BufWriter, Write, self};
-        let file = File::create(FILE_PATH).expect("Failed to create file");
-        let mut writer = BufWriter::new(file);
-        for entry in entries {
-            writer.write_all(entry.as_bytes()).expect("Failed to write to file");
-            writer.flush().expect("Failed to flush file");
+    fn write_to_file(entries: &[serde_json::Value]) {
+        if let Some(dir) = std::path::Path::new(FILE_PATH).parent() {
+            std::fs::create_dir_all(dir).expect("Failed to create output directory");
         }
-
+        let json = serde_json::to_string_pretty(entries).expect("Failed to serialize entries");
+        std::fs::write(FILE_PATH, json).expect("Failed to write to file");
     }
-    let music_files = collect_music_files();
+    let music_files = collect_music_files().expect("Failed to read music folder");
     let mut file_string: String;
-    let mut artist: String;
-    let mut title: String;
-    let mut album: String;
-    let mut genre: String;
-    let mut entries: Vec<String> = Vec::new();
+    let mut entries: Vec<serde_json::Value> = Vec::new();
     for music_file in music_files {
         
         match music_file.to_str() {
@@ -38,44 +30,22 @@ fn test_reading_metadata() {
             None => file_string = String::from("File String"),
         }
         let metadata = SongMetadata::from_file(music_file).unwrap();
-        let artist = match metadata.artist {
-            Some(ref n) if is_roman_alphabet(n) => n.clone(),
-            _ => "Unknown Artist".to_string(),
-        };
-
-        let album = match metadata.album {
-            Some(ref n) if is_roman_alphabet(n) => n.clone(),
-            _ => "Unknown Album".to_string(),
-        };
-
-        let title = match metadata.title {
-            Some(ref n) if is_roman_alphabet(n) => n.clone(),
-            _ => "Unknown Title".to_string(),
-        };
-
-        let genre = match metadata.genre {
-            Some(ref n) if is_roman_alphabet(n) => n.clone(),
-            _ => "Unknown Genre".to_string(),
-        };
-        entries.push(format!(r#"
-{{
-    "Path": "{file_string}",
-    "Artist": "{artist}",
-    "Album": "{album}",
-    "Title": "{title}",
-    "Genre": "{genre}"
-}},
-"#, 
-        artist = artist,
-        album = album,
-        title = title,
-        genre = genre,
-        ));
+        let artist = readable(&metadata.artist, "Unknown Artist");
+        let album = readable(&metadata.album, "Unknown Album");
+        let title = readable(&metadata.title, "Unknown Title");
+        let genre = readable(&metadata.genre, "Unknown Genre");
+        entries.push(serde_json::json!({
+            "Path": file_string,
+            "Artist": artist,
+            "Album": album,
+            "Title": title,
+            "Genre": genre,
+        }));
 
 
     }
-    write_to_file(entries.clone());
+    write_to_file(&entries);
     for entry in entries {
-        println!("{}",entry);
+        println!("{:#}",entry);
     }
 }
\ No newline at end of file
@@ -0,0 +1,58 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn track(meta: &SongMetadata) -> (Option<u32>, Option<u32>) {
+    (meta.track_number, meta.track_total)
+}
+
+#[test]
+fn test_id3v2_trck() {
+    let cases = [("3/12", (Some(3), Some(12))), ("7", (Some(7), None)), ("3/", (Some(3), None)), ("side A", (None, None))];
+    for (value, expected) in cases {
+        let path = write_temp("trck", "mp3", &mp3_file(&[id3_text(b"TRCK", value)]));
+        assert_eq!(track(&SongMetadata::from_file(&path).unwrap()), expected, "TRCK {value:?}");
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_vorbis_track_comments() {
+    let path = write_temp("vorbis_track", "flac", &flac_file(&[
+        (4, vorbis_comments(&["TRACKNUMBER=5", "TRACKTOTAL=9"])),
+    ]));
+    assert_eq!(track(&SongMetadata::from_file(&path).unwrap()), (Some(5), Some(9)));
+
+    fs::write(&path, flac_file(&[(4, vorbis_comments(&["TOTALTRACKS=9", "TRACKNUMBER=5/10"]))])).unwrap();
+    assert_eq!(track(&SongMetadata::from_file(&path).unwrap()), (Some(5), Some(10)));
+
+    fs::write(&path, flac_file(&[(4, vorbis_comments(&["TRACKNUMBER=five"]))])).unwrap();
+    assert_eq!(track(&SongMetadata::from_file(&path).unwrap()), (None, None));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_wav_itrk() {
+    let info = [b"INFO".to_vec(), riff_chunk(b"ITRK", b"4\0")].concat();
+    let path = write_temp("itrk", "wav", &wav_file(&[riff_chunk(b"LIST", &info)], 0));
+    assert_eq!(track(&SongMetadata::from_file(&path).unwrap()), (Some(4), None));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_id3v1_track_byte() {
+    let mut bytes = vec![0u8; 64];
+    bytes.extend(id3v1_tag("Title", "Artist", "Album", "1999", 11, 13));
+    let path = write_temp("v1_track", "mp3", &bytes);
+    assert_eq!(track(&SongMetadata::from_file(&path).unwrap()), (Some(11), None));
+
+    // ID3v1.0: byte 125 is still comment text, so byte 126 is not a track
+    let last = bytes.len() - 3;
+    bytes[last] = b'x';
+    fs::write(&path, &bytes).unwrap();
+    assert_eq!(track(&SongMetadata::from_file(&path).unwrap()), (None, None));
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::*;
+use meta::{ParseWarning, SongMetadata};
+
+use std::fs;
+
+fn bad_frame(id: &[u8; 4], claimed_size: u32) -> Vec<u8> {
+    let mut frame = id.to_vec();
+    frame.extend_from_slice(&claimed_size.to_be_bytes());
+    frame.extend_from_slice(&[0, 0]);
+    frame.extend_from_slice(b"corrupt picture data");
+    frame
+}
+
+#[test]
+fn test_verbose_skips_frame_that_overruns_the_tag() {
+    let body = [
+        id3_text(b"TIT2", "Song"),
+        bad_frame(b"APIC", 0x0FFF_FFFF),
+        id3_text(b"TPE1", "Artist"),
+        vec![0; 32],
+    ].concat();
+    let path = write_temp("verbose_overrun", "mp3", &[id3v2_tag(3, 0, &body), mp3_frames(2, false)].concat());
+
+    // the quiet parser stops at the damaged frame
+    let quiet = SongMetadata::from_file(&path).unwrap();
+    assert_eq!((quiet.title.as_deref(), quiet.artist.as_deref()), (Some("Song"), None));
+
+    let (meta, warnings) = SongMetadata::from_file_verbose(&path).unwrap();
+    assert_eq!((meta.title.as_deref(), meta.artist.as_deref()), (Some("Song"), Some("Artist")));
+    let offset = id3_text(b"TIT2", "Song").len();
+    assert_eq!(warnings, [ParseWarning { frame: "APIC".into(), offset, reason: "frame size exceeds the tag" }]);
+    assert!(warnings[0].to_string().contains("APIC"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_verbose_reports_empty_and_garbled_frames() {
+    let empty = bad_frame(b"TXXX", 0)[..10].to_vec();
+    let garbled = b"\x01\x02xy\0\0\0\x02\0\0ab".to_vec();
+    for (damaged, reason) in [(empty, "empty frame"), (garbled, "invalid frame ID")] {
+        let body = [damaged, id3_text(b"TALB", "Album")].concat();
+        let path = write_temp("verbose_garbled", "mp3", &id3v2_tag(3, 0, &body));
+
+        let (meta, warnings) = SongMetadata::from_file_verbose(&path).unwrap();
+        assert_eq!(meta.album.as_deref(), Some("Album"));
+        let reasons: Vec<_> = warnings.iter().map(|w| (w.offset, w.reason)).collect();
+        assert_eq!(reasons, [(0, reason)]);
+
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_verbose_clean_tag_has_no_warnings() {
+    let path = write_temp("verbose_clean", "mp3", &mp3_file(&[id3_text(b"TIT2", "Song")]));
+    let (meta, warnings) = SongMetadata::from_file_verbose(&path).unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Song"));
+    assert!(warnings.is_empty());
+    fs::remove_file(path).unwrap();
+}
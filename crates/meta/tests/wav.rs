@@ -0,0 +1,119 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+#[test]
+fn test_wav_bext_chunk() {
+    let bext = riff_chunk(b"bext", &bext_body("Field recording, dawn chorus", "Recorder X1", "2023-04-01", "05:42:10", 1_058_400_000));
+    let path = write_temp("bext", "wav", &wav_file(&[bext], 1_000));
+
+    let meta = SongMetadata::from_file(&path).unwrap();
+    let bext = meta.bext.expect("bext chunk");
+    assert_eq!(bext.description.as_deref(), Some("Field recording, dawn chorus"));
+    assert_eq!(bext.origination_date.as_deref(), Some("2023-04-01"));
+    assert_eq!(bext.origination_time.as_deref(), Some("05:42:10"));
+    assert_eq!(bext.originator.as_deref(), Some("Recorder X1"));
+    // 06:40:00 at 44.1 kHz
+    assert_eq!(bext.time_reference, 1_058_400_000);
+    fs::remove_file(path).unwrap();
+}
+
+fn info_list(items: &[(&[u8; 4], &str)]) -> Vec<u8> {
+    let mut body = b"INFO".to_vec();
+    for (id, text) in items {
+        body.extend(riff_chunk(id, text.as_bytes()));
+    }
+    riff_chunk(b"LIST", &body)
+}
+
+#[test]
+fn test_wav_without_or_with_short_bext() {
+    let path = write_temp("no_bext", "wav", &wav_file(&[], 1_000));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().bext, None);
+    fs::remove_file(path).unwrap();
+
+    let short = riff_chunk(b"bext", &[b' '; 300]);
+    let path = write_temp("short_bext", "wav", &wav_file(&[short], 1_000));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().bext, None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_wav_id3_chunk_overrides_info() {
+    let info = info_list(&[(b"INAM", "Info Title"), (b"IART", "Info Artist"), (b"IPRD", "Info Album")]);
+    let id3 = id3v2_tag(3, 0, &[id3_text(b"TIT2", "ID3 Title"), id3_text(b"TPE1", "ID3 Artist")].concat());
+    // the id3 chunk may come before or after INFO
+    for (name, chunks) in [("wav_id3_after", [info.clone(), riff_chunk(b"id3 ", &id3)]), ("wav_id3_before", [riff_chunk(b"ID3 ", &id3), info.clone()])] {
+        let path = write_temp(name, "wav", &wav_file(&chunks, 1_000));
+        let meta = SongMetadata::from_file(&path).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("ID3 Title"));
+        assert_eq!(meta.artist.as_deref(), Some("ID3 Artist"));
+        // INFO fills in what the ID3 tag lacks
+        assert_eq!(meta.album.as_deref(), Some("Info Album"));
+        assert_eq!(meta.sample_rate, Some(44_100));
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_wav_malformed_id3_chunk_is_ignored() {
+    let info = info_list(&[(b"INAM", "Info Title")]);
+    let path = write_temp("wav_bad_id3", "wav", &wav_file(&[info, riff_chunk(b"id3 ", b"not a tag")], 1_000));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().title.as_deref(), Some("Info Title"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_wav_list_shorter_than_its_type() {
+    for size in 0u32..4 {
+        let mut list = b"LIST".to_vec();
+        list.extend_from_slice(&size.to_le_bytes());
+        list.extend_from_slice(&b"INFO"[..size as usize]);
+        if size % 2 == 1 {
+            list.push(0);
+        }
+        let info = info_list(&[(b"INAM", "After")]);
+        let meta = SongMetadata::from_bytes(&wav_file(&[list, info], 1_000)).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("After"), "LIST size {size}");
+    }
+}
+
+#[test]
+fn test_wav_info_subchunk_larger_than_list() {
+    let mut body = b"INFO".to_vec();
+    body.extend(riff_chunk(b"IART", b"Artist"));
+    body.extend_from_slice(b"INAM");
+    body.extend_from_slice(&u32::MAX.to_le_bytes());
+    body.extend_from_slice(b"Title");
+    let meta = SongMetadata::from_bytes(&wav_file(&[riff_chunk(b"LIST", &body)], 1_000)).unwrap();
+    assert_eq!(meta.artist.as_deref(), Some("Artist"));
+    assert_eq!(meta.title, None);
+}
+
+#[test]
+fn test_wav_many_empty_chunks() {
+    let junk = vec![riff_chunk(b"JUNK", &[]); 100_000];
+    let meta = SongMetadata::from_bytes(&wav_file(&junk, 1_000)).unwrap();
+    assert_eq!(meta.artist, None);
+}
+
+#[test]
+fn test_wav_mutated_list_does_not_panic() {
+    let clean = wav_file(&[info_list(&[(b"INAM", "Title"), (b"IART", "Artist"), (b"ITRK", "3/12")])], 64);
+    let list_at = clean.windows(4).position(|w| w == b"LIST").unwrap();
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    for _ in 0..2_000 {
+        let mut bytes = clean.clone();
+        for _ in 0..4 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let at = list_at + (state as usize >> 8) % (clean.len() - list_at);
+            bytes[at] = state as u8;
+        }
+        let _ = SongMetadata::from_bytes(&bytes);
+    }
+}
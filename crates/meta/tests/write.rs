@@ -0,0 +1,164 @@
+mod common;
+
+use common::*;
+use meta::{MetaError, SongMetadata};
+
+use std::fs;
+
+fn edited() -> SongMetadata {
+    SongMetadata {
+        title: Some("Ünïcode Title".into()),
+        artist: Some("Artist".into()),
+        album: Some("Album".into()),
+        genre: Some("Jazz".into()),
+        track_number: Some(3),
+        track_total: Some(12),
+        ..SongMetadata::default()
+    }
+}
+
+fn assert_fields(m: &SongMetadata) {
+    assert_eq!(m.title.as_deref(), Some("Ünïcode Title"));
+    assert_eq!(m.artist.as_deref(), Some("Artist"));
+    assert_eq!(m.album.as_deref(), Some("Album"));
+    assert_eq!(m.genre.as_deref(), Some("Jazz"));
+    assert_eq!((m.track_number, m.track_total), (Some(3), Some(12)));
+}
+
+#[test]
+fn test_write_adds_tag_to_untagged_mp3() {
+    let audio = mp3_frames(10, false);
+    let path = write_temp("write_untagged", "mp3", &audio);
+
+    edited().write_to_file(&path).unwrap();
+    let m = SongMetadata::from_file(&path).unwrap();
+    assert_fields(&m);
+    assert_eq!(m.duration_ms, Some(261));
+
+    let bytes = fs::read(&path).unwrap();
+    assert_eq!(&bytes[0..4], b"ID3\x04");
+    assert!(bytes.ends_with(&audio));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_write_replaces_existing_tag_and_keeps_other_frames() {
+    let audio = mp3_frames(10, false);
+    let mut bytes = mp3_file(&[
+        id3_text(b"TIT2", "Old Title"),
+        id3_text(b"TPE1", "Old Artist"),
+        apic(3, "image/png", b"\x89PNG cover"),
+    ]);
+    bytes.extend_from_slice(&audio);
+    let path = write_temp("write_tagged", "mp3", &bytes);
+
+    let mut m = SongMetadata::from_file(&path).unwrap();
+    assert_eq!(m.title.as_deref(), Some("Old Title"));
+    m.title = Some("Ünïcode Title".into());
+    m.artist = Some("Artist".into());
+    m.album = Some("Album".into());
+    m.genre = Some("Jazz".into());
+    (m.track_number, m.track_total) = (Some(3), Some(12));
+    m.write_to_file(&path).unwrap();
+
+    assert_fields(&SongMetadata::from_file(&path).unwrap());
+    assert_eq!(SongMetadata::cover_art(&path).unwrap().unwrap().data, b"\x89PNG cover");
+    assert!(fs::read(&path).unwrap().ends_with(&audio));
+
+    // writing again is stable
+    SongMetadata::from_file(&path).unwrap().write_to_file(&path).unwrap();
+    assert_fields(&SongMetadata::from_file(&path).unwrap());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_write_keeps_multiple_artists() {
+    let path = write_temp("write_artists", "mp3", &mp3_frames(4, false));
+    let m = SongMetadata {
+        artist: Some("A".into()),
+        artists: vec!["A".into(), "B".into()],
+        ..SongMetadata::default()
+    };
+    m.write_to_file(&path).unwrap();
+    assert_eq!(SongMetadata::from_file(&path).unwrap().artists, ["A", "B"]);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_write_rejects_other_formats() {
+    let path = write_temp("write_flac", "flac", &flac_file(&[]));
+    assert!(matches!(edited().write_to_file(&path), Err(MetaError::UnsupportedFormat)));
+    fs::remove_file(path).unwrap();
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[test]
+fn test_write_converts_v23_only_frames() {
+    let mut bytes = mp3_file(&[id3_text(b"TYER", "2003"), id3_text(b"TDAT", "0105"), id3_text(b"TSIZ", "1234")]);
+    bytes.extend_from_slice(&mp3_frames(4, false));
+    let path = write_temp("write_v23_frames", "mp3", &bytes);
+
+    edited().write_to_file(&path).unwrap();
+    let tag = fs::read(&path).unwrap();
+    assert!(contains(&tag, b"TDRC"));
+    assert!(!contains(&tag, b"TYER") && !contains(&tag, b"TDAT") && !contains(&tag, b"TSIZ"));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().year, Some(2003));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_write_keeps_frames_with_data_length_indicator() {
+    // a v2.4 COMM frame flagged 0x01: a synchsafe length ahead of the body
+    let comment = [&[0u8][..], b"eng\0Kept"].concat();
+    let mut frame = id3v24_frame(b"COMM", &[&synchsafe(comment.len() as u32)[..], &comment].concat());
+    frame[9] = 0x01;
+    let mut bytes = id3v2_tag(4, 0, &frame);
+    bytes.extend_from_slice(&mp3_frames(4, false));
+    let path = write_temp("write_dli", "mp3", &bytes);
+
+    edited().write_to_file(&path).unwrap();
+    let tag = fs::read(&path).unwrap();
+    assert!(contains(&tag, &id3v24_frame(b"COMM", &comment)));
+    assert_eq!(SongMetadata::from_file(&path).unwrap().comment.as_deref(), Some("Kept"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_write_leaves_no_temp_file() {
+    let path = write_temp("write_temp_file", "mp3", &mp3_frames(4, false));
+    edited().write_to_file(&path).unwrap();
+
+    let name = path.file_name().unwrap().to_string_lossy().into_owned();
+    let leftovers = fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(&format!(".{name}")))
+        .count();
+    assert_eq!(leftovers, 0);
+    assert_fields(&SongMetadata::from_file(&path).unwrap());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_write_over_damaged_appended_tag() {
+    // the appended tag's header claims more than the file holds, which
+    // fails a full parse, but the frames and the footer still mark an MP3
+    let body = id3v24_frame(b"TIT2", &[&[3u8][..], b"Old Title"].concat());
+    let mut tag = id3v2_tag(4, 0x10, &body);
+    tag[6..10].copy_from_slice(&synchsafe(0x0FFF_FFFF));
+    tag.extend_from_slice(b"3DI\x04\0\x10");
+    tag.extend_from_slice(&synchsafe(body.len() as u32));
+    let audio = mp3_frames(4, false);
+    let bytes = [audio.clone(), tag].concat();
+    assert!(SongMetadata::from_bytes(&bytes).is_err());
+    let path = write_temp("write_damaged_appended", "mp3", &bytes);
+
+    edited().write_to_file(&path).unwrap();
+    let written = fs::read(&path).unwrap();
+    assert_eq!(&written[0..4], b"ID3\x04");
+    assert!(contains(&written, &audio));
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::*;
+use meta::SongMetadata;
+
+use std::fs;
+
+fn year_of(name: &str, ext: &str, bytes: &[u8]) -> Option<i32> {
+    let path = write_temp(name, ext, bytes);
+    let year = SongMetadata::from_file(&path).unwrap().year;
+    fs::remove_file(path).unwrap();
+    year
+}
+
+#[test]
+fn test_id3v2_year_frames() {
+    assert_eq!(year_of("tyer", "mp3", &mp3_file(&[id3_text(b"TYER", "1997")])), Some(1997));
+    assert_eq!(year_of("tdrc", "mp3", &mp3_file(&[id3_text(b"TDRC", "2021-06-15T10:00")])), Some(2021));
+    assert_eq!(year_of("tdrc_bad", "mp3", &mp3_file(&[id3_text(b"TDRC", "'97")])), None);
+}
+
+#[test]
+fn test_vorbis_m4a_and_wav_year() {
+    let flac = flac_file(&[(4, vorbis_comments(&["DATE=2021-06-15"]))]);
+    assert_eq!(year_of("vorbis_date", "flac", &flac), Some(2021));
+    let flac = flac_file(&[(4, vorbis_comments(&["YEAR=1984"]))]);
+    assert_eq!(year_of("vorbis_year", "flac", &flac), Some(1984));
+
+    let m4a = m4a_file(&[atom(b"\xa9day", &data_atom(1, b"2008-03-01T08:00:00Z"))]);
+    assert_eq!(year_of("day", "m4a", &m4a), Some(2008));
+
+    let info = [b"INFO".to_vec(), riff_chunk(b"ICRD", b"2003-11-02\0\0")].concat();
+    assert_eq!(year_of("icrd", "wav", &wav_file(&[riff_chunk(b"LIST", &info)], 0)), Some(2003));
+}
+
+#[test]
+fn test_id3v1_year() {
+    let mut bytes = vec![0u8; 64];
+    bytes.extend(id3v1_tag("Title", "Artist", "Album", "1999", 0, 13));
+    assert_eq!(year_of("v1_year", "mp3", &bytes), Some(1999));
+
+    let mut bytes = vec![0u8; 64];
+    bytes.extend(id3v1_tag("Title", "Artist", "Album", "", 0, 13));
+    assert_eq!(year_of("v1_no_year", "mp3", &bytes), None);
+}
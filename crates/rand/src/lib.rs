@@ -1,5 +1,8 @@
 use std::time::{UNIX_EPOCH, SystemTime};
 use std::ops::{Sub, Add, Rem};
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
 
 pub trait ToFromI128 {
     fn to_i128(self) -> i128;
@@ -28,16 +31,40 @@ impl_to_from_i128!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
 
 
 
-fn random_base() -> i128 {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        let mut x = nanos;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        x.to_i128()
+thread_local! {
+    /// Backs [`RandomInt::new`]: seeded once per thread, then advanced, so
+    /// values drawn in a tight loop are independent.
+    static THREAD_RNG: RefCell<Rng> = RefCell::new(Rng::seed_from_entropy());
+}
+
+/// Seed material from the system clock, mixed with std's per-process random
+/// hash keys so threads started in the same instant still differ.
+fn clock_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    nanos ^ RandomState::new().build_hasher().finish()
+}
+
+/// A random `u64` from this thread's generator.
+///
+/// The free functions share a per-thread [`Rng`] seeded from entropy, so
+/// their output is deliberately not reproducible; use an explicitly seeded
+/// [`Rng`] when you need the same stream twice.
+pub fn random_u64() -> u64 {
+    THREAD_RNG.with_borrow_mut(Rng::next_u64)
+}
+
+/// A value in `min..=max` from this thread's generator; see [`RandomInt::new`].
+pub fn gen_range<T>(min: T, max: T) -> T
+where
+    T: Copy + ToFromI128 + Add<Output = T> + Sub<Output = T> + Rem<Output = T>, u32: Add<T>{
+    RandomInt::new(min, max)
+}
+
+/// Shuffles `slice` in place with this thread's generator; see [`Rng::shuffle`].
+pub fn shuffle<T>(slice: &mut [T]) {
+    THREAD_RNG.with_borrow_mut(|rng| rng.shuffle(slice))
 }
 
 pub struct RandomInt<T> {
@@ -48,18 +75,230 @@ pub struct RandomInt<T> {
 impl<T> RandomInt<T>
 where
     T: Copy + ToFromI128 + Add<Output = T> + Sub<Output = T> + Rem<Output = T>, u32: Add<T>{
+    /// A value in `min..=max` from this thread's generator. The bounds may be
+    /// given in either order: `new(10, 5)` draws from `5..=10`.
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(min: T, max: T) -> T {
-        let rng = Self { min, max };
-        rng.random()
+        THREAD_RNG.with_borrow_mut(|rng| Self::from_rng(rng, min, max))
+    }
 
+    /// A value in `min..=max` drawn from `rng`, advancing it. Like [`new`](Self::new),
+    /// inverted bounds are swapped.
+    pub fn from_rng(rng: &mut Rng, min: T, max: T) -> T {
+        let range = Self { min, max };
+        range.random(rng)
     }
 
-    fn random(&self) -> T {
-        let min = self.min.to_i128();
-        let max = self.max.to_i128() + 1;
+    fn random(&self, rng: &mut Rng) -> T {
+        let (lo, hi) = (self.min.to_i128(), self.max.to_i128());
+        let (min, max) = if lo > hi { (hi, lo) } else { (lo, hi) };
+        let max = max + 1;
         let range = (max.wrapping_sub(min)).max(1);
-        let r = random_base() % range;
+        let r = match u64::try_from(range) {
+            Ok(n) => rng.below(n) as i128,
+            // only 128-bit spans get here; reject draws past the largest
+            // multiple of `range` so every remainder is equally likely
+            Err(_) => {
+                let range = range as u128;
+                let zone = u128::MAX - u128::MAX % range;
+                loop {
+                    let wide = (u128::from(rng.next_u64()) << 64) | u128::from(rng.next_u64());
+                    if wide < zone {
+                        break (wide % range) as i128;
+                    }
+                }
+            }
+        };
         T::from_i128(r.wrapping_add(min))
     }
     
 }
+
+/// One SplitMix64 step, used to spread seed material over the whole state.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A xorshift64* generator that keeps its state between calls.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Seeds the generator from a single number. The same seed always yields
+    /// the identical stream, across runs and platforms, so tests can rely on
+    /// exact output.
+    pub fn from_seed(seed: u64) -> Self {
+        Self::with_state(splitmix64(seed))
+    }
+
+    /// Seeds the generator from the system clock: a different stream on
+    /// every run.
+    pub fn seed_from_entropy() -> Self {
+        Self::from_seed(clock_seed())
+    }
+
+    /// Seeds the generator from arbitrary bytes: a file hash, a UUID, OS
+    /// entropy, or e.g. `utils::USERNAME` for a shuffle order that is stable
+    /// across sessions but differs per user. Equal bytes give equal streams.
+    pub fn from_seed_bytes(bytes: &[u8]) -> Self {
+        let mut state = splitmix64(bytes.len() as u64);
+        for chunk in bytes.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            state = splitmix64(state ^ u64::from_le_bytes(word));
+        }
+        Self::with_state(state)
+    }
+
+    /// xorshift has a fixed point at zero, so it is never used as state.
+    fn with_state(state: u64) -> Self {
+        Self {
+            state: if state == 0 { 0x9E37_79B9_7F4A_7C15 } else { state },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Fills `buf` with random bytes, eight per [`next_u64`](Self::next_u64)
+    /// (little-endian), with a final partial word for any remainder. The
+    /// stream continues across calls.
+    pub fn fill_bytes(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let word = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+    }
+
+    /// `n` random bytes; see [`fill_bytes`](Self::fill_bytes).
+    pub fn gen_bytes(&mut self, n: usize) -> Vec<u8> {
+        let mut buf = vec![0; n];
+        self.fill_bytes(&mut buf);
+        buf
+    }
+
+    /// Uniform value in the half-open interval `[0, 1)`: the top 53 bits of
+    /// [`next_u64`](Self::next_u64) divided by `2^53`, so every result is an
+    /// exactly representable multiple of `2^-53` and there is no rounding bias.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform value in `[min, max)`. Returns `min` when the range is empty
+    /// (`max <= min`) or not finite.
+    pub fn gen_range_f64(&mut self, min: f64, max: f64) -> f64 {
+        let span = max - min;
+        if !span.is_finite() || span <= 0.0 {
+            return min;
+        }
+        loop {
+            let v = min + span * self.next_f64();
+            // rounding can land exactly on `max` for some ranges
+            if v < max {
+                return v;
+            }
+        }
+    }
+
+    /// `true` with probability `p`. Values outside `[0, 1]` are clamped to
+    /// the nearest bound, and NaN counts as `0`.
+    pub fn gen_bool(&mut self, p: f64) -> bool {
+        self.next_f64() < p.clamp(0.0, 1.0)
+    }
+
+    /// A fair coin flip: [`gen_bool`](Self::gen_bool) with `p = 0.5`.
+    pub fn flip(&mut self) -> bool {
+        self.gen_bool(0.5)
+    }
+
+    /// Uniform value in `0..n` (`n > 0`) by Lemire's multiply-and-reject
+    /// method, which avoids the bias of a plain `% n`.
+    fn below(&mut self, n: u64) -> u64 {
+        let mut m = u128::from(self.next_u64()) * u128::from(n);
+        if (m as u64) < n {
+            let threshold = n.wrapping_neg() % n;
+            while (m as u64) < threshold {
+                m = u128::from(self.next_u64()) * u128::from(n);
+            }
+        }
+        (m >> 64) as u64
+    }
+
+    /// A uniformly chosen element of `slice`, or `None` if it is empty.
+    pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        if slice.is_empty() {
+            return None;
+        }
+        slice.get(self.below(slice.len() as u64) as usize)
+    }
+
+    /// A random `[A-Za-z0-9]` string of `len` characters, e.g. for temporary
+    /// IDs. Not suitable for secrets.
+    pub fn alphanumeric(&mut self, len: usize) -> String {
+        const ALPHANUMERIC: &[u8; 62] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+        (0..len).map(|_| ALPHANUMERIC[self.below(62) as usize] as char).collect()
+    }
+
+    /// A string of `len` characters each chosen uniformly from `charset`.
+    /// Returns an empty string for `len == 0` or an empty `charset`.
+    pub fn string_from(&mut self, charset: &[char], len: usize) -> String {
+        (0..len).map_while(|_| self.choose(charset)).collect()
+    }
+
+    /// Shuffles `slice` in place (Fisher–Yates). Given a good seed every
+    /// permutation is equally likely.
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            slice.swap(i, j);
+        }
+    }
+
+    /// A shuffled copy of `slice`, leaving the original order untouched.
+    pub fn shuffled<T: Clone>(&mut self, slice: &[T]) -> Vec<T> {
+        let mut copy = slice.to_vec();
+        self.shuffle(&mut copy);
+        copy
+    }
+
+    /// Uniform value in `(0, 1]` from the top 53 bits; never zero, so it is
+    /// safe to take the logarithm of.
+    fn next_unit_open_zero(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a random permutation of `0..weights.len()` in which items with
+    /// larger weights tend to come first (weighted sampling without
+    /// replacement, Efraimidis–Spirakis). Each item gets the key
+    /// `rand^(1/weight)`, computed as `ln(rand) / weight`, and indices are
+    /// ordered by descending key. Items with a zero, negative or NaN weight
+    /// are placed last in their original order. Use the indices to reorder a
+    /// queue and any parallel arrays.
+    pub fn shuffle_weighted(&mut self, weights: &[f64]) -> Vec<usize> {
+        let mut keyed: Vec<(f64, usize)> = weights
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| {
+                let key = if w > 0.0 {
+                    self.next_unit_open_zero().ln() / w
+                } else {
+                    f64::NEG_INFINITY
+                };
+                (key, i)
+            })
+            .collect();
+        keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+        keyed.into_iter().map(|(_, i)| i).collect()
+    }
+}
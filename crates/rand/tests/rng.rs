@@ -0,0 +1,270 @@
+use rand::{RandomInt, Rng};
+
+#[test]
+fn test_seed_bytes_are_reproducible() {
+    let mut a = Rng::from_seed_bytes(b"rosary-user");
+    let mut b = Rng::from_seed_bytes(b"rosary-user");
+    for _ in 0..100 {
+        assert_eq!(a.next_u64(), b.next_u64());
+    }
+}
+
+#[test]
+fn test_seed_bytes_differ_per_input() {
+    let first = |seed: &[u8]| Rng::from_seed_bytes(seed).next_u64();
+    assert_ne!(first(b"alice"), first(b"bob"));
+    // trailing zero bytes still change the seed
+    assert_ne!(first(b"abc"), first(b"abc\0"));
+    // an empty seed is valid and doesn't get stuck at zero
+    let mut empty = Rng::from_seed_bytes(&[]);
+    assert_ne!(empty.next_u64(), empty.next_u64());
+}
+
+#[test]
+fn test_shuffle_weighted_is_a_permutation() {
+    let mut rng = Rng::from_seed_bytes(b"weighted");
+    let mut order = rng.shuffle_weighted(&[1.0, 5.0, 0.5, 2.0, 3.0]);
+    order.sort();
+    assert_eq!(order, vec![0, 1, 2, 3, 4]);
+    assert!(rng.shuffle_weighted(&[]).is_empty());
+}
+
+#[test]
+fn test_shuffle_weighted_favours_heavy_items() {
+    let mut rng = Rng::from_seed_bytes(b"favourites");
+    let weights = [1.0, 20.0, 1.0, 1.0];
+    let trials = 2_000;
+    let heavy_first = (0..trials)
+        .filter(|_| rng.shuffle_weighted(&weights)[0] == 1)
+        .count();
+    // expected share is 20/23 ≈ 0.87
+    assert!(heavy_first > trials * 8 / 10, "heavy item first only {heavy_first} times");
+}
+
+#[test]
+fn test_shuffle_weighted_puts_unweighted_last() {
+    let mut rng = Rng::from_seed_bytes(b"zeros");
+    for _ in 0..50 {
+        let order = rng.shuffle_weighted(&[0.0, 1.0, -2.0, 3.0, f64::NAN]);
+        assert_eq!(&order[2..], &[0, 2, 4]);
+    }
+}
+
+#[test]
+fn test_random_int_from_rng_stays_in_range() {
+    let mut rng = Rng::from_seed(7);
+    for _ in 0..1_000 {
+        let v = RandomInt::from_rng(&mut rng, 10u32, 20);
+        assert!((10..=20).contains(&v));
+    }
+    assert_eq!(RandomInt::from_rng(&mut rng, 3u32, 3), 3);
+}
+
+#[test]
+fn test_random_int_new_does_not_repeat_in_tight_loop() {
+    // reseeding from the clock on every call used to return runs of equal values
+    let values: Vec<u32> = (0..64).map(|_| RandomInt::new(0u32, u32::MAX - 1)).collect();
+    let mut distinct = values.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), values.len());
+}
+
+#[test]
+fn test_equal_seeds_give_equal_streams() {
+    let mut a = Rng::from_seed(42);
+    let mut b = Rng::from_seed(42);
+    for _ in 0..100 {
+        assert_eq!(a.next_u64(), b.next_u64());
+    }
+    assert_ne!(Rng::from_seed(42).next_u64(), Rng::from_seed(43).next_u64());
+}
+
+#[test]
+fn test_from_seed_stream_is_stable() {
+    // pinned so a change to the algorithm can't silently break reproducibility
+    let mut rng = Rng::from_seed(0);
+    let first: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+    assert_eq!(first, [8916199331640804048, 16032783972208265725, 12954103179475586193]);
+}
+
+#[test]
+fn test_seed_from_entropy_differs() {
+    assert_ne!(Rng::seed_from_entropy().next_u64(), Rng::seed_from_entropy().next_u64());
+}
+
+#[test]
+fn test_next_f64_is_in_unit_interval() {
+    let mut rng = Rng::from_seed(1);
+    let samples: Vec<f64> = (0..10_000).map(|_| rng.next_f64()).collect();
+    assert!(samples.iter().all(|v| (0.0..1.0).contains(v)));
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    assert!((mean - 0.5).abs() < 0.02, "mean {mean}");
+}
+
+#[test]
+fn test_gen_range_f64() {
+    let mut rng = Rng::from_seed(2);
+    for _ in 0..10_000 {
+        let v = rng.gen_range_f64(-2.5, 4.0);
+        assert!((-2.5..4.0).contains(&v));
+    }
+    assert_eq!(rng.gen_range_f64(3.0, 3.0), 3.0);
+    assert_eq!(rng.gen_range_f64(5.0, 1.0), 5.0);
+    assert_eq!(rng.gen_range_f64(0.0, f64::NAN), 0.0);
+}
+
+#[test]
+fn test_gen_bool_probability() {
+    let mut rng = Rng::from_seed(3);
+    let trials = 10_000;
+    let hits = (0..trials).filter(|_| rng.gen_bool(0.2)).count();
+    assert!((1_800..2_200).contains(&hits), "{hits} hits");
+    let heads = (0..trials).filter(|_| rng.flip()).count();
+    assert!((4_700..5_300).contains(&heads), "{heads} heads");
+}
+
+#[test]
+fn test_gen_bool_clamps_probability() {
+    let mut rng = Rng::from_seed(4);
+    for _ in 0..1_000 {
+        assert!(rng.gen_bool(1.0));
+        assert!(rng.gen_bool(7.5));
+        assert!(!rng.gen_bool(0.0));
+        assert!(!rng.gen_bool(-1.0));
+        assert!(!rng.gen_bool(f64::NAN));
+    }
+}
+
+#[test]
+fn test_choose_reaches_every_element() {
+    let mut rng = Rng::from_seed(5);
+    let tracks = ["intro", "single", "ballad", "b-side", "outro"];
+    let mut seen = [0usize; 5];
+    for _ in 0..5_000 {
+        let pick = rng.choose(&tracks).unwrap();
+        seen[tracks.iter().position(|t| t == pick).unwrap()] += 1;
+    }
+    // each expected ~1000 times
+    assert!(seen.iter().all(|&n| (850..1_150).contains(&n)), "{seen:?}");
+
+    assert_eq!(rng.choose::<u8>(&[]), None);
+    assert_eq!(rng.choose(&[9]), Some(&9));
+}
+
+#[test]
+fn test_shuffle_is_uniform_per_position() {
+    let mut rng = Rng::from_seed(6);
+    let n = 5;
+    let trials = 20_000;
+    let mut counts = vec![vec![0u32; n]; n];
+    for _ in 0..trials {
+        let mut items: Vec<usize> = (0..n).collect();
+        rng.shuffle(&mut items);
+        for (position, &item) in items.iter().enumerate() {
+            counts[position][item] += 1;
+        }
+    }
+    let expected = trials as f64 / n as f64;
+    let chi_square: f64 = counts.iter().flatten()
+        .map(|&c| (c as f64 - expected).powi(2) / expected)
+        .sum();
+    // 16 degrees of freedom for the 5x5 table; 40 is far past the 0.1% tail
+    assert!(chi_square < 40.0, "chi-square {chi_square}");
+}
+
+#[test]
+fn test_shuffled_keeps_original() {
+    let mut rng = Rng::from_seed(8);
+    let queue = vec!["a", "b", "c", "d", "e", "f"];
+    let mut shuffled = rng.shuffled(&queue);
+    assert_eq!(queue, ["a", "b", "c", "d", "e", "f"]);
+    shuffled.sort();
+    assert_eq!(shuffled, queue);
+
+    let mut empty: [u8; 0] = [];
+    rng.shuffle(&mut empty);
+    let mut one = [1];
+    rng.shuffle(&mut one);
+    assert_eq!(one, [1]);
+}
+
+#[test]
+fn test_random_int_small_range_is_even() {
+    let mut rng = Rng::from_seed(9);
+    let trials = 60_000;
+    let mut counts = [0u32; 6];
+    for _ in 0..trials {
+        counts[(RandomInt::from_rng(&mut rng, 1u32, 6) - 1) as usize] += 1;
+    }
+    // each face expected 10_000 times; 3% is several standard deviations
+    assert!(counts.iter().all(|&c| c.abs_diff(10_000) < 300), "{counts:?}");
+}
+
+#[test]
+fn test_random_int_swaps_inverted_bounds() {
+    let mut rng = Rng::from_seed(10);
+    let mut seen = [false; 6];
+    for _ in 0..1_000 {
+        let v = RandomInt::from_rng(&mut rng, 10u32, 5);
+        assert!((5..=10).contains(&v), "{v}");
+        seen[(v - 5) as usize] = true;
+    }
+    assert!(seen.iter().all(|&s| s));
+    assert!((5..=10).contains(&RandomInt::new(10u32, 5)));
+}
+
+#[test]
+fn test_thread_local_free_functions() {
+    assert_ne!(rand::random_u64(), rand::random_u64());
+    for _ in 0..100 {
+        assert!((1..=3).contains(&rand::gen_range(1u32, 3)));
+    }
+    let mut queue = [1, 2, 3, 4, 5, 6, 7, 8];
+    rand::shuffle(&mut queue);
+    queue.sort();
+    assert_eq!(queue, [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_fill_bytes_matches_u64_stream() {
+    let mut a = Rng::from_seed(11);
+    let mut b = Rng::from_seed(11);
+    let bytes = a.gen_bytes(13);
+    assert_eq!(bytes.len(), 13);
+    let expected: Vec<u8> = [b.next_u64(), b.next_u64()].iter().flat_map(|w| w.to_le_bytes()).collect();
+    assert_eq!(bytes, expected[..13]);
+
+    // the next call continues the stream instead of repeating it
+    let mut buf = [0u8; 8];
+    a.fill_bytes(&mut buf);
+    assert_eq!(buf, b.next_u64().to_le_bytes());
+    assert!(a.gen_bytes(0).is_empty());
+}
+
+#[test]
+fn test_alphanumeric_strings() {
+    let mut rng = Rng::from_seed(12);
+    let id = rng.alphanumeric(32);
+    assert_eq!(id.len(), 32);
+    assert!(id.chars().all(|c| c.is_ascii_alphanumeric()));
+    assert_ne!(id, rng.alphanumeric(32));
+    assert_eq!(rng.alphanumeric(0), "");
+
+    // every character of the alphabet turns up
+    let long = rng.alphanumeric(10_000);
+    let mut distinct: Vec<char> = long.chars().collect();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), 62);
+}
+
+#[test]
+fn test_string_from_custom_charset() {
+    let mut rng = Rng::from_seed(13);
+    let s = rng.string_from(&['あ', 'い', 'う'], 20);
+    assert_eq!(s.chars().count(), 20);
+    assert!(s.chars().all(|c| "あいう".contains(c)));
+    assert_eq!(rng.string_from(&['x'], 0), "");
+    assert_eq!(rng.string_from(&[], 5), "");
+}
@@ -1,7 +1,11 @@
 use std::sync::LazyLock;
 use std::path::PathBuf;
 use std::fs;
+use std::io;
 use std::path::Path;
+use std::time::SystemTime;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
 
 
 #[cfg(target_os = "windows")]
@@ -9,12 +13,26 @@ pub static USERNAME: LazyLock<String> = LazyLock::new(|| {
     std::env::var("USERNAME").unwrap_or_else(|_| "unknown".to_string())
 });
 
+// Linux, Android and macOS all expose the login name as $USER
 #[cfg(not(target_os = "windows"))]
 pub static USERNAME: LazyLock<String> = LazyLock::new(|| {
     std::env::var("USER").unwrap_or_else(|_| "unknown".to_string())
 });
 
+/// The music library root, resolved once in this order:
+/// 1. the `MUSIC_FOLDER` environment variable,
+/// 2. the XDG `XDG_MUSIC_DIR` environment variable,
+/// 3. the per-OS default (`~/Music`, or the shared storage folder on Android).
+///
+/// Empty variables count as unset.
 pub static MUSIC_FOLDER_PATH: LazyLock<String> = LazyLock::new(|| {
+    ["MUSIC_FOLDER", "XDG_MUSIC_DIR"]
+        .into_iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_else(default_music_folder)
+});
+
+fn default_music_folder() -> String {
     #[cfg(target_os = "android")]
     {
         "/storage/emulated/0/Music".to_string()
@@ -27,7 +45,11 @@ pub static MUSIC_FOLDER_PATH: LazyLock<String> = LazyLock::new(|| {
     {
         format!("/home/{}/Music", *USERNAME)
     }
-});
+    #[cfg(target_os = "macos")]
+    {
+        format!("/Users/{}/Music", *USERNAME)
+    }
+}
 
 pub static SCANFILE_PATH: LazyLock<String> = LazyLock::new(|| {
     #[cfg(target_os = "windows")]
@@ -48,64 +70,447 @@ pub static SCANFILE_PATH: LazyLock<String> = LazyLock::new(|| {
     }
 });
 
-pub fn collect_music_files() -> Vec<PathBuf> {
-    let supported = ["mp3", "m4a", "wav", "flac"];
-    // Check if path exists and is a directory
-    let path = Path::new(&*MUSIC_FOLDER_PATH);
-    
-    if !path.exists() {
-        eprintln!("Error: Path '{}' does not exist.", &*MUSIC_FOLDER_PATH);
+/// File extensions (lowercase) treated as music.
+pub const SUPPORTED_EXTENSIONS: [&str; 8] = ["mp3", "m4a", "wav", "flac", "ogg", "opus", "aiff", "aif"];
+
+/// Visits every file under `root` that a scan keeps: its extension is in
+/// `extensions` (compared case-insensitively, without the dot), or it sniffs
+/// as audio when `options.sniff` is set, and it isn't a hidden or empty file
+/// the options skip. Subdirectories are walked when `options.recursive` is
+/// set, using an explicit stack instead of recursion; symlinked directories
+/// are never followed, so links can't create cycles.
+///
+/// Fails if `root` itself can't be read; entries and subdirectories that fail
+/// individually are reported and skipped.
+fn walk_files(root: &Path, extensions: &[&str], options: &ScanOptions, mut visit: impl FnMut(&fs::DirEntry)) -> io::Result<()> {
+    let supported: Vec<String> = extensions.iter().map(|ext| ext.to_lowercase()).collect();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if dir == root => return Err(e),
+            Err(e) => {
+                eprintln!("Error reading directory {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+        for entry_result in entries {
+            let entry = match entry_result {
+                Ok(entry) => entry,
+                Err(e) => {
+                    eprintln!("Error reading entry: {}", e);
+                    continue;
+                }
+            };
+            // DirEntry::file_type doesn't traverse symlinks
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(e) => {
+                    eprintln!("Error reading file type of {}: {}", entry.path().display(), e);
+                    continue;
+                }
+            };
+            if file_type.is_dir() {
+                if options.recursive {
+                    pending.push(entry.path());
+                } else {
+                    println!("(Skipping directory) {}", entry.path().display());
+                }
+            } else if file_type.is_file() {
+                if keep_file(&entry, &supported, options) {
+                    visit(&entry);
+                }
+            } else {
+                println!("(Other) {}", entry.path().display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The per-file half of [`walk_files`]: hidden, empty and extension checks.
+fn keep_file(entry: &fs::DirEntry, supported: &[String], options: &ScanOptions) -> bool {
+    if options.skip_hidden && entry.file_name().to_string_lossy().starts_with('.') {
+        #[cfg(debug_assertions)]
+        println!("Skipped File: {}\nFor Reason: Hidden file", entry.path().display());
+        return false;
+    }
+    if options.skip_empty && entry.metadata().is_ok_and(|m| m.len() == 0) {
+        #[cfg(debug_assertions)]
+        println!("Skipped File: {}\nFor Reason: Empty file", entry.path().display());
+        return false;
+    }
+    let extension: Option<String> = entry.path().extension()
+        .and_then(|ext| ext.to_str()) // Convert OsStr to &str
+        .map(|ext_str| ext_str.to_lowercase());
+    match extension {
+        Some(n) if supported.contains(&n) => true,
+        _ if options.sniff && sniff_audio(&entry.path()) => true,
+        _ => {
+            #[cfg(debug_assertions)]
+            println!("Skipped File: {}\nFor Reason: Unsupported extension", entry.path().display());
+            false
+        }
+    }
+}
+
+/// The order every collector returns: case-insensitive by file name, then by
+/// full path, since `read_dir` order is up to the OS.
+fn sort_music_files(music_files: &mut [PathBuf]) {
+    music_files.sort_by_cached_key(|path| {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase());
+        (name, path.clone())
+    });
+}
+
+/// Returns the `n` music files under `root` with the newest modification
+/// time, newest first. Keeps a bounded min-heap of size `n` rather than
+/// sorting every path in the library. Fails if `root` can't be read.
+pub fn recently_added(root: &Path, n: usize) -> io::Result<Vec<PathBuf>> {
+    if n == 0 {
+        return Ok(Vec::new());
     }
-    if !path.is_dir() {
-        eprintln!("Error: '{}' is not a directory.", &*MUSIC_FOLDER_PATH);
+    let options = ScanOptions { recursive: true, ..ScanOptions::default() };
+    let mut newest: BinaryHeap<Reverse<(SystemTime, PathBuf)>> = BinaryHeap::with_capacity(n + 1);
+    walk_files(root, &SUPPORTED_EXTENSIONS, &options, |entry| {
+        let Ok(modified) = entry.metadata().and_then(|m| m.modified()) else {
+            return;
+        };
+        newest.push(Reverse((modified, entry.path())));
+        if newest.len() > n {
+            newest.pop();
+        }
+    })?;
+    // ascending order of Reverse is descending mtime
+    Ok(newest.into_sorted_vec().into_iter().map(|Reverse((_, path))| path).collect())
+}
+
+/// Lists the supported music files directly inside `MUSIC_FOLDER_PATH`.
+/// Fails if the folder itself can't be read; entries that fail individually
+/// are reported and skipped.
+pub fn collect_music_files() -> io::Result<Vec<PathBuf>> {
+    collect_music_files_with(Path::new(&*MUSIC_FOLDER_PATH), &SUPPORTED_EXTENSIONS)
+}
+
+/// Like [`collect_music_files`], but lists `root` and keeps only files whose
+/// extension is in `extensions` (compared case-insensitively, without the dot),
+/// e.g. `&["flac"]` for a FLAC-only scan.
+pub fn collect_music_files_with(root: &Path, extensions: &[&str]) -> io::Result<Vec<PathBuf>> {
+    collect_music_files_filtered(root, extensions, &ScanOptions::default())
+}
+
+/// Which files a scan passes over regardless of extension, and whether it
+/// descends into subfolders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// Skip files whose name starts with `.` (`._track.mp3` resource forks,
+    /// editor temp files). Turn off if the library genuinely stores dotfiles.
+    pub skip_hidden: bool,
+    /// Skip zero-byte files, which can't contain any audio.
+    pub skip_empty: bool,
+    /// Also keep files without a recognised extension when their first bytes
+    /// look like a supported container (see [`sniff_audio`]), e.g. downloads
+    /// saved without an extension.
+    pub sniff: bool,
+    /// Walk subfolders too (e.g. `Artist/Album/track.flac`). Off by default,
+    /// which lists only the top level.
+    pub recursive: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions { skip_hidden: true, skip_empty: true, sniff: false, recursive: false }
+    }
+}
+
+/// Whether the file at `path` starts like an audio file `meta` can read:
+/// `RIFF`/`WAVE`, `FORM`/`AIFF`, `fLaC`, `OggS`, an `ID3` tag, or an MP4
+/// `ftyp` atom. Only the first 12 bytes are read.
+pub fn sniff_audio(path: &Path) -> bool {
+    let mut header = Vec::with_capacity(12);
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    if io::Read::read_to_end(&mut io::Read::take(file, 12), &mut header).is_err() {
+        return false;
     }
-    
-    // Read directory entries
+    let at = |range: std::ops::Range<usize>| header.get(range).unwrap_or_default();
+    // a whole 10-byte tag header, not just the magic
+    (header.len() >= 10 && header.starts_with(b"ID3"))
+        || matches!(at(0..4), b"fLaC" | b"OggS")
+        || (at(0..4) == b"RIFF" && at(8..12) == b"WAVE")
+        || (at(0..4) == b"FORM" && matches!(at(8..12), b"AIFF" | b"AIFC"))
+        || at(4..8) == b"ftyp"
+}
+
+/// [`collect_music_files_with`] with explicit [`ScanOptions`].
+pub fn collect_music_files_filtered(root: &Path, extensions: &[&str], options: &ScanOptions) -> io::Result<Vec<PathBuf>> {
     let mut music_files: Vec<PathBuf> = Vec::new();
-    for entry_result in fs::read_dir(path).expect("Failed to read directory") {
-        match entry_result {
-            Ok(entry) => {
-                let file_type = entry.file_type().expect("Could not find file type");
-                if file_type.is_file() {
-                    let extension:Option<String> = Path::new(&entry.path().display().to_string()).extension()
-                         .and_then(|ext| ext.to_str()) // Convert OsStr to &str
-                         .map(|ext_str| ext_str.to_lowercase());
-                    match extension {
-                        Some(n) => {
-                            if supported.contains(&n.as_str()) {
-                                music_files.push(Path::new(&entry.path().display().to_string()).to_path_buf());
-                            }
-                            else {
-                                #[cfg(debug_assertions)]
-                                println!("Skipped File: {}\nFor Reason: Unsupported extension", entry.path().display());
-                            }
-                        },
-                        None => {
-                            #[cfg(debug_assertions)]
-                            println!("Skipped File: {}\nFor Reason: Unsupported extension", entry.path().display());
-                        },
-                    }
-                    
-                } else if file_type.is_dir() {
-                    println!("(Skipping directory) {}", entry.path().display());
-                } else {
-                    println!("(Other) {}", entry.path().display());
+    walk_files(root, extensions, options, |entry| music_files.push(entry.path()))?;
+    sort_music_files(&mut music_files);
+    Ok(music_files)
+}
+
+/// [`collect_music_files`] with a caller-chosen order, e.g. album/track order
+/// once metadata has been read.
+pub fn collect_music_files_sorted_by<F>(compare: F) -> io::Result<Vec<PathBuf>>
+where
+    F: FnMut(&PathBuf, &PathBuf) -> Ordering,
+{
+    let mut music_files = collect_music_files()?;
+    music_files.sort_by(compare);
+    Ok(music_files)
+}
+
+/// Collects the music files under `root`, in the same order and with the
+/// same errors as [`collect_music_files`]. With `recursive` set,
+/// subdirectories (e.g. `Artist/Album/track.flac`) are walked too, using an
+/// explicit stack; symlinked directories are never followed, so links can't
+/// create cycles.
+pub fn collect_from(root: &Path, recursive: bool) -> io::Result<Vec<PathBuf>> {
+    let options = ScanOptions { recursive, ..ScanOptions::default() };
+    collect_music_files_filtered(root, &SUPPORTED_EXTENSIONS, &options)
+}
+
+/// [`collect_from`] over `MUSIC_FOLDER_PATH`, including every subfolder.
+pub fn collect_music_files_recursive() -> io::Result<Vec<PathBuf>> {
+    collect_from(Path::new(&*MUSIC_FOLDER_PATH), true)
+}
+
+/// Collects music files from several library roots (e.g. internal storage,
+/// an SD card and `Download` on Android). Each root is scanned like
+/// [`collect_music_files_filtered`] with the same `extensions` and `options`
+/// (set `options.recursive` to walk subfolders); a file reachable from more
+/// than one root (nested roots, bind mounts) is returned once,
+/// judged by canonical path. Roots are visited in the order given and files
+/// within a root are sorted like [`collect_music_files`], so the result is
+/// stable between runs. A root that doesn't exist (an unmounted SD card) is
+/// skipped; one that exists but can't be read is an error.
+pub fn collect_music_files_multi(roots: &[PathBuf], extensions: &[&str], options: &ScanOptions) -> io::Result<Vec<PathBuf>> {
+    let mut seen = HashSet::new();
+    let mut music_files = Vec::new();
+    for root in roots {
+        let found = match collect_music_files_filtered(root, extensions, options) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            result => result?,
+        };
+        for path in found {
+            let key = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            if seen.insert(key) {
+                music_files.push(path);
+            }
+        }
+    }
+    Ok(music_files)
+}
+
+/// A file recorded by a previous library scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanEntry {
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
+}
+
+impl ScanEntry {
+    /// Records `path` along with its current modification time, if readable.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        Self { path, modified }
+    }
+}
+
+/// Paths that changed between a cached scan and the files currently on disk.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RescanResult {
+    pub added: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
+
+/// Drops entries whose path no longer exists (deleted, trashed, or moved away)
+/// and returns the removed paths so the caller can update its views.
+pub fn prune_missing(entries: &mut Vec<ScanEntry>) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    entries.retain(|entry| {
+        if entry.path.exists() {
+            true
+        } else {
+            removed.push(entry.path.clone());
+            false
+        }
+    });
+    removed
+}
+
+/// Merges a fresh directory listing into a cached scan: gone files are pruned
+/// and files not yet in `entries` are appended.
+pub fn merge_scan(entries: &mut Vec<ScanEntry>, found: Vec<PathBuf>) -> RescanResult {
+    let removed = prune_missing(entries);
+    // a set rather than a search of `entries` per path, for large libraries;
+    // inserting found paths also drops duplicates within `found`
+    let mut known: HashSet<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
+    let added: Vec<PathBuf> = found.into_iter().filter(|path| known.insert(path.clone())).collect();
+    entries.extend(added.iter().map(|path| ScanEntry::new(path.clone())));
+    RescanResult { added, removed }
+}
+
+/// The "refresh library" operation: re-reads the music folder and brings
+/// `entries` up to date with it. `entries` is left untouched if the folder
+/// can't be read.
+pub fn rescan(entries: &mut Vec<ScanEntry>) -> io::Result<RescanResult> {
+    Ok(merge_scan(entries, collect_music_files()?))
+}
+
+/// Longest sanitized name in bytes, leaving room for an extension within the
+/// common 255-byte filename limit.
+const MAX_FILENAME_BYTES: usize = 200;
+
+/// Makes a tag-derived string (e.g. `"Artist - Title"`) safe to use as a file
+/// name on Windows, macOS and Linux: characters illegal on any of them and
+/// control characters become `_`, trailing dots/spaces are dropped, Windows
+/// reserved device names (`CON`, `COM1`, ...) get a `_` suffix, and the result
+/// is truncated on a character boundary. The extension is left to the caller.
+pub fn sanitize_filename(s: &str) -> String {
+    let mut name: String = s
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    if name.len() > MAX_FILENAME_BYTES {
+        let mut end = MAX_FILENAME_BYTES;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name.truncate(end);
+    }
+
+    let mut name = name.trim_start().trim_end_matches(['.', ' ']).to_string();
+    if name.is_empty() {
+        return "_".to_string();
+    }
+
+    // Windows refuses these even with an extension, e.g. `nul.mp3`
+    let stem = name.split('.').next().unwrap_or("").trim_end().to_ascii_uppercase();
+    let reserved = matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || ((stem.starts_with("COM") || stem.starts_with("LPT"))
+            && stem.len() == 4
+            && stem.as_bytes()[3].is_ascii_digit()
+            && stem.as_bytes()[3] != b'0');
+    if reserved {
+        let stem_end = name.find('.').unwrap_or(name.len());
+        name.insert(stem_end, '_');
+    }
+    name
+}
+
+/// Latin spelling of a lowercase Cyrillic or Greek letter.
+fn transliterate_lower(c: char) -> Option<&'static str> {
+    let latin = match c {
+        // Cyrillic (Russian, plus Ukrainian/Belarusian/Serbian letters)
+        'а' => "a", 'б' => "b", 'в' => "v", 'г' => "g", 'д' => "d", 'е' => "e",
+        'ё' => "yo", 'ж' => "zh", 'з' => "z", 'и' => "i", 'й' => "y", 'к' => "k",
+        'л' => "l", 'м' => "m", 'н' => "n", 'о' => "o", 'п' => "p", 'р' => "r",
+        'с' => "s", 'т' => "t", 'у' => "u", 'ф' => "f", 'х' => "kh", 'ц' => "ts",
+        'ч' => "ch", 'ш' => "sh", 'щ' => "shch", 'ъ' => "", 'ы' => "y", 'ь' => "",
+        'э' => "e", 'ю' => "yu", 'я' => "ya", 'є' => "ye", 'і' => "i", 'ї' => "yi",
+        'ґ' => "g", 'ў' => "u", 'ђ' => "dj", 'ј' => "j", 'љ' => "lj", 'њ' => "nj",
+        'ћ' => "c", 'џ' => "dz",
+        // Greek, including tonos/dialytika forms
+        'α' | 'ά' => "a", 'β' => "v", 'γ' => "g", 'δ' => "d", 'ε' | 'έ' => "e",
+        'ζ' => "z", 'η' | 'ή' => "i", 'θ' => "th", 'ι' | 'ί' | 'ϊ' | 'ΐ' => "i",
+        'κ' => "k", 'λ' => "l", 'μ' => "m", 'ν' => "n", 'ξ' => "x", 'ο' | 'ό' => "o",
+        'π' => "p", 'ρ' => "r", 'σ' | 'ς' => "s", 'τ' => "t", 'υ' | 'ύ' | 'ϋ' | 'ΰ' => "y",
+        'φ' => "f", 'χ' => "ch", 'ψ' => "ps", 'ω' | 'ώ' => "o",
+        _ => return None,
+    };
+    Some(latin)
+}
+
+/// Best-effort transliteration of Cyrillic and Greek text to Latin letters
+/// (e.g. `"Кино"` → `"Kino"`), so non-Latin artists and titles can be shown
+/// readably instead of being replaced with "Unknown". Capitalisation is kept
+/// on the first Latin letter. Everything else, including CJK scripts which
+/// are out of scope for a table-based approach, passes through unchanged.
+pub fn romanize(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        match transliterate_lower(lower) {
+            Some(latin) if lower != c => {
+                let mut letters = latin.chars();
+                if let Some(first) = letters.next() {
+                    out.extend(first.to_uppercase());
+                    out.push_str(letters.as_str());
                 }
             }
-            Err(e) => eprintln!("Error reading entry: {}", e),
+            Some(latin) => out.push_str(latin),
+            None => out.push(c),
         }
     }
-    music_files
+    out
+}
+
+/// The writing system a tag value is (mostly) written in, as reported by
+/// [`detect_script`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+    Arabic,
+    /// Chinese, Japanese (including kana) and Korean.
+    Cjk,
+    /// No script holds a majority of the letters, or the majority is in a
+    /// script not listed here.
+    Mixed,
+    /// No letters at all: empty, or only digits, spaces and punctuation.
+    Empty,
+}
+
+fn script_of(c: char) -> Option<Script> {
+    let script = match c as u32 {
+        0x41..=0x5A | 0x61..=0x7A | 0xC0..=0x24F | 0x1E00..=0x1EFF => Script::Latin,
+        0x370..=0x3FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x400..=0x52F => Script::Cyrillic,
+        0x600..=0x6FF | 0x750..=0x77F | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => Script::Arabic,
+        0x1100..=0x11FF | 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF
+        | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0x20000..=0x2FFFF => Script::Cjk,
+        _ => return None,
+    };
+    Some(script)
+}
+
+/// Classifies `s` by the script more than half of its letters belong to, so
+/// a UI can pick a suitable font for e.g. `"坂本龍一"` (`Cjk`) or `"Кино"`
+/// (`Cyrillic`). Digits, spaces and punctuation don't count either way.
+pub fn detect_script(s: &str) -> Script {
+    let mut counts: Vec<(Option<Script>, usize)> = Vec::new();
+    let mut letters = 0;
+    for c in s.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        let script = script_of(c);
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+    if letters == 0 {
+        return Script::Empty;
+    }
+    counts.into_iter()
+        .find(|&(_, n)| n * 2 > letters)
+        .and_then(|(script, _)| script)
+        .unwrap_or(Script::Mixed)
 }
 
-pub fn is_roman_alphabet(s: String) -> bool {
-    let x = s.as_str();
-    x.chars().all(|c| {
-        c.is_ascii_alphabetic() ||
-        c.is_ascii_digit() ||
-        c.is_ascii_whitespace() ||
-        c.is_ascii_punctuation()
-    })
+/// Whether `s` is mostly Latin text, i.e. [`detect_script`] says
+/// [`Script::Latin`]. Strings without letters are not.
+pub fn is_roman_alphabet(s: &str) -> bool {
+    detect_script(s) == Script::Latin
 }
 
 
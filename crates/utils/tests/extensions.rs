@@ -0,0 +1,41 @@
+use utils::collect_music_files_with;
+
+use std::fs;
+
+#[test]
+fn test_collect_music_files_with_custom_extensions() {
+    let base = std::env::temp_dir().join(format!("utils_extensions_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    fs::create_dir_all(&base).unwrap();
+    for name in ["a.flac", "b.FLAC", "c.mp3", "d.mka"] {
+        fs::write(base.join(name), b"audio").unwrap();
+    }
+
+    let flac_only = collect_music_files_with(&base, &["flac"]).unwrap();
+    assert_eq!(flac_only, vec![base.join("a.flac"), base.join("b.FLAC")]);
+
+    // the allowlist may name formats the default list doesn't, in any case
+    assert_eq!(collect_music_files_with(&base, &["MKA"]).unwrap(), vec![base.join("d.mka")]);
+    assert!(collect_music_files_with(&base, &[]).unwrap().is_empty());
+    assert!(collect_music_files_with(&base.join("missing"), &["flac"]).is_err());
+
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn test_collect_music_files_with_is_sorted_case_insensitively() {
+    let base = std::env::temp_dir().join(format!("utils_sorted_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    fs::create_dir_all(&base).unwrap();
+    for name in ["b.mp3", "C.mp3", "a.mp3", "B2.mp3"] {
+        fs::write(base.join(name), b"audio").unwrap();
+    }
+
+    let names: Vec<String> = collect_music_files_with(&base, &["mp3"]).unwrap()
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, ["a.mp3", "b.mp3", "B2.mp3", "C.mp3"]);
+
+    fs::remove_dir_all(&base).unwrap();
+}
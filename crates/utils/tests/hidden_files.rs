@@ -0,0 +1,44 @@
+use utils::{collect_music_files_filtered, collect_music_files_with, ScanOptions, SUPPORTED_EXTENSIONS};
+
+use std::fs;
+
+#[test]
+fn test_hidden_and_empty_files_are_skipped_by_default() {
+    let base = std::env::temp_dir().join(format!("utils_hidden_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    fs::create_dir_all(&base).unwrap();
+    fs::write(base.join("song.mp3"), b"audio").unwrap();
+    fs::write(base.join("._song.mp3"), b"resource fork").unwrap();
+    fs::write(base.join("empty.flac"), b"").unwrap();
+
+    assert_eq!(collect_music_files_with(&base, &SUPPORTED_EXTENSIONS).unwrap(), vec![base.join("song.mp3")]);
+
+    let keep_hidden = ScanOptions { skip_hidden: false, ..ScanOptions::default() };
+    assert_eq!(
+        collect_music_files_filtered(&base, &SUPPORTED_EXTENSIONS, &keep_hidden).unwrap(),
+        vec![base.join("._song.mp3"), base.join("song.mp3")]
+    );
+
+    let keep_all = ScanOptions { skip_hidden: false, skip_empty: false, ..ScanOptions::default() };
+    assert_eq!(collect_music_files_filtered(&base, &SUPPORTED_EXTENSIONS, &keep_all).unwrap().len(), 3);
+
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn test_hidden_and_empty_files_are_skipped_in_subfolders() {
+    let base = std::env::temp_dir().join(format!("utils_hidden_nested_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    fs::create_dir_all(base.join("Album")).unwrap();
+    fs::write(base.join("Album/01.mp3"), b"audio").unwrap();
+    fs::write(base.join("Album/.01.mp3"), b"editor temp file").unwrap();
+    fs::write(base.join("Album/02.mp3"), b"").unwrap();
+
+    assert_eq!(utils::collect_from(&base, true).unwrap(), vec![base.join("Album/01.mp3")]);
+    assert_eq!(utils::recently_added(&base, 10).unwrap(), vec![base.join("Album/01.mp3")]);
+    let recursive = ScanOptions { recursive: true, ..ScanOptions::default() };
+    let multi = utils::collect_music_files_multi(std::slice::from_ref(&base), &SUPPORTED_EXTENSIONS, &recursive);
+    assert_eq!(multi.unwrap(), vec![base.join("Album/01.mp3")]);
+
+    fs::remove_dir_all(&base).unwrap();
+}
@@ -0,0 +1,59 @@
+use utils::{collect_music_files_multi, ScanOptions, SUPPORTED_EXTENSIONS};
+
+use std::fs;
+
+#[test]
+fn test_collect_music_files_multi_dedupes_and_orders() {
+    let base = std::env::temp_dir().join(format!("utils_multi_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    let music = base.join("Music");
+    let download = base.join("Download");
+    fs::create_dir_all(music.join("Album")).unwrap();
+    fs::create_dir_all(&download).unwrap();
+
+    fs::write(music.join("b.mp3"), b"audio").unwrap();
+    fs::write(music.join("Album/a.flac"), b"audio").unwrap();
+    fs::write(music.join("cover.jpg"), b"audio").unwrap();
+    fs::write(download.join("single.m4a"), b"audio").unwrap();
+
+    // the nested root overlaps with `music`, so its files must not repeat
+    let roots = vec![download.clone(), music.clone(), music.join("Album"), base.join("missing")];
+    let recursive = ScanOptions { recursive: true, ..ScanOptions::default() };
+    let found = collect_music_files_multi(&roots, &SUPPORTED_EXTENSIONS, &recursive).unwrap();
+    assert_eq!(
+        found,
+        vec![download.join("single.m4a"), music.join("Album/a.flac"), music.join("b.mp3")]
+    );
+    assert_eq!(collect_music_files_multi(&roots, &SUPPORTED_EXTENSIONS, &recursive).unwrap(), found);
+
+    // top level only, and only FLAC
+    let top_level = collect_music_files_multi(&roots, &["flac"], &ScanOptions::default()).unwrap();
+    assert_eq!(top_level, vec![music.join("Album/a.flac")]);
+
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn test_collect_music_files_multi_applies_scan_options() {
+    let base = std::env::temp_dir().join(format!("utils_multi_options_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    let (first, second) = (base.join("First"), base.join("Second"));
+    fs::create_dir_all(&first).unwrap();
+    fs::create_dir_all(&second).unwrap();
+
+    fs::write(first.join("song.mp3"), b"audio").unwrap();
+    fs::write(first.join(".song.mp3"), b"editor temp file").unwrap();
+    fs::write(first.join("empty.mp3"), b"").unwrap();
+    fs::write(second.join("other.mp3"), b"audio").unwrap();
+
+    let roots = vec![first.clone(), second.clone()];
+    assert_eq!(
+        collect_music_files_multi(&roots, &SUPPORTED_EXTENSIONS, &ScanOptions::default()).unwrap(),
+        vec![first.join("song.mp3"), second.join("other.mp3")]
+    );
+
+    let keep_all = ScanOptions { skip_hidden: false, skip_empty: false, ..ScanOptions::default() };
+    assert_eq!(collect_music_files_multi(&roots, &SUPPORTED_EXTENSIONS, &keep_all).unwrap().len(), 4);
+
+    fs::remove_dir_all(&base).unwrap();
+}
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 #[test]
 fn test_music_files_collection() {
 
-    let music_files: Vec<PathBuf> = collect_music_files();
+    let music_files: Vec<PathBuf> = collect_music_files().expect("Failed to read music folder");
 
     for music_file in music_files {
         match music_file.to_str() {
@@ -16,4 +16,24 @@ fn test_music_files_collection() {
             None => println!("Path contains invalid UTF-8"),
         }
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_rescan_reports_unreadable_folder_or_succeeds() {
+    let mut entries = Vec::new();
+    // whether or not the folder exists here, a missing one must be an Err, not a panic
+    match utils::rescan(&mut entries) {
+        Ok(result) => assert_eq!(result.added.len(), entries.len()),
+        Err(_) => assert!(entries.is_empty()),
+    }
+}
+
+#[test]
+fn test_music_files_sorted_by_comparator() {
+    let Ok(default_order) = collect_music_files() else {
+        return;
+    };
+    let custom = utils::collect_music_files_sorted_by(|a, b| b.cmp(a)).unwrap();
+    let mut expected = default_order;
+    expected.sort_by(|a, b| b.cmp(a));
+    assert_eq!(custom, expected);
+}
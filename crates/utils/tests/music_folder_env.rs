@@ -0,0 +1,24 @@
+use utils::{collect_music_files, MUSIC_FOLDER_PATH};
+
+use std::fs;
+
+// Kept alone in its own test binary: MUSIC_FOLDER_PATH is resolved once per
+// process, so the override has to be in place before anything reads it.
+#[test]
+fn test_music_folder_env_override() {
+    let base = std::env::temp_dir().join(format!("utils_music_env_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    fs::create_dir_all(&base).unwrap();
+    fs::write(base.join("track.flac"), b"audio").unwrap();
+
+    // SAFETY: the only test in this binary, so no other thread reads the environment
+    unsafe {
+        std::env::set_var("XDG_MUSIC_DIR", "/nonexistent/xdg/Music");
+        std::env::set_var("MUSIC_FOLDER", &base);
+    }
+
+    assert_eq!(*MUSIC_FOLDER_PATH, base.to_str().unwrap());
+    assert_eq!(collect_music_files().unwrap(), vec![base.join("track.flac")]);
+
+    fs::remove_dir_all(&base).unwrap();
+}
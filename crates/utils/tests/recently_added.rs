@@ -0,0 +1,33 @@
+use utils::recently_added;
+
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+fn touch(path: &Path, age_secs: u64) {
+    fs::write(path, b"audio").unwrap();
+    let file = File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::now() - Duration::from_secs(age_secs)).unwrap();
+}
+
+#[test]
+fn test_recently_added_returns_newest_first() {
+    let root = std::env::temp_dir().join(format!("utils_recent_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("Artist/Album")).unwrap();
+
+    touch(&root.join("old.mp3"), 500);
+    touch(&root.join("Artist/Album/newest.flac"), 10);
+    touch(&root.join("Artist/middle.M4A"), 100);
+    touch(&root.join("Artist/Album/notes.txt"), 0);
+    touch(&root.join("older.wav"), 1000);
+
+    let recent = recently_added(&root, 2).unwrap();
+    assert_eq!(recent, vec![root.join("Artist/Album/newest.flac"), root.join("Artist/middle.M4A")]);
+
+    assert_eq!(recently_added(&root, 10).unwrap().len(), 4);
+    assert!(recently_added(&root, 0).unwrap().is_empty());
+    assert!(recently_added(&root.join("missing"), 3).is_err());
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,41 @@
+use utils::collect_from;
+
+use std::fs;
+
+#[test]
+fn test_collect_from_descends_only_when_recursive() {
+    let base = std::env::temp_dir().join(format!("utils_recursive_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    fs::create_dir_all(base.join("Artist/Album")).unwrap();
+
+    fs::write(base.join("loose.MP3"), b"audio").unwrap();
+    fs::write(base.join("notes.txt"), b"audio").unwrap();
+    fs::write(base.join("Artist/Album/01.flac"), b"audio").unwrap();
+    fs::write(base.join("Artist/Album/cover.jpg"), b"audio").unwrap();
+    fs::write(base.join("Artist/single.ogg"), b"audio").unwrap();
+
+    assert_eq!(collect_from(&base, false).unwrap(), vec![base.join("loose.MP3")]);
+    // sorted by file name, like the top-level collectors
+    assert_eq!(
+        collect_from(&base, true).unwrap(),
+        vec![base.join("Artist/Album/01.flac"), base.join("loose.MP3"), base.join("Artist/single.ogg")]
+    );
+    assert!(collect_from(&base.join("missing"), true).is_err());
+
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_collect_from_does_not_follow_symlinked_directories() {
+    let base = std::env::temp_dir().join(format!("utils_symlink_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    fs::create_dir_all(base.join("Album")).unwrap();
+    fs::write(base.join("Album/track.mp3"), b"audio").unwrap();
+    // a link back to the root would loop forever if followed
+    std::os::unix::fs::symlink(&base, base.join("Album/loop")).unwrap();
+
+    assert_eq!(collect_from(&base, true).unwrap(), vec![base.join("Album/track.mp3")]);
+
+    fs::remove_dir_all(&base).unwrap();
+}
@@ -0,0 +1,22 @@
+use utils::{is_roman_alphabet, romanize};
+
+#[test]
+fn test_romanize_cyrillic() {
+    assert_eq!(romanize("Кино"), "Kino");
+    assert_eq!(romanize("Земфира"), "Zemfira");
+    assert_eq!(romanize("Щедрик"), "Shchedrik");
+    assert_eq!(romanize("ЖАРА"), "ZhARA");
+    assert!(is_roman_alphabet(&romanize("Группа крови")));
+}
+
+#[test]
+fn test_romanize_greek() {
+    assert_eq!(romanize("Βαγγέλης"), "Vaggelis");
+    assert_eq!(romanize("Θεοδωράκης"), "Theodorakis");
+}
+
+#[test]
+fn test_romanize_leaves_other_text_alone() {
+    assert_eq!(romanize("Daft Punk - One More Time"), "Daft Punk - One More Time");
+    assert_eq!(romanize("坂本龍一"), "坂本龍一");
+}
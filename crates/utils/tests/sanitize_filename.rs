@@ -0,0 +1,30 @@
+use utils::sanitize_filename;
+
+#[test]
+fn test_path_separators_in_title() {
+    assert_eq!(sanitize_filename("AC/DC - Back In Black"), "AC_DC - Back In Black");
+    assert_eq!(sanitize_filename(r"..\..\etc/passwd"), ".._.._etc_passwd");
+    assert_eq!(sanitize_filename("What? <Live>: \"Take 2\" | *"), "What_ _Live__ _Take 2_ _ _");
+}
+
+#[test]
+fn test_reserved_names() {
+    assert_eq!(sanitize_filename("CON"), "CON_");
+    assert_eq!(sanitize_filename("nul.mp3"), "nul_.mp3");
+    assert_eq!(sanitize_filename("Com1"), "Com1_");
+    assert_eq!(sanitize_filename("LPT9"), "LPT9_");
+    assert_eq!(sanitize_filename("COM0"), "COM0");
+    assert_eq!(sanitize_filename("Console"), "Console");
+}
+
+#[test]
+fn test_trailing_dots_spaces_and_length() {
+    assert_eq!(sanitize_filename("Title... "), "Title");
+    assert_eq!(sanitize_filename(" . "), "_");
+    assert_eq!(sanitize_filename("Tab\there"), "Tab_here");
+
+    let long = "é".repeat(150);
+    let cleaned = sanitize_filename(&long);
+    assert!(cleaned.len() <= 200);
+    assert!(cleaned.chars().all(|c| c == 'é'));
+}
@@ -0,0 +1,57 @@
+use utils::{merge_scan, prune_missing, ScanEntry};
+
+use std::fs;
+use std::path::PathBuf;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("utils_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed to create scratch directory");
+    dir
+}
+
+#[test]
+fn test_prune_missing_drops_deleted_files() {
+    let dir = scratch_dir("prune");
+    let kept = dir.join("kept.mp3");
+    let gone = dir.join("gone.mp3");
+    fs::write(&kept, b"a").unwrap();
+    fs::write(&gone, b"b").unwrap();
+
+    let mut entries = vec![ScanEntry::new(&kept), ScanEntry::new(&gone)];
+    assert!(entries[1].modified.is_some());
+    fs::remove_file(&gone).unwrap();
+
+    let removed = prune_missing(&mut entries);
+    assert_eq!(removed, vec![gone]);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].path, kept);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_merge_scan_adds_and_removes() {
+    let dir = scratch_dir("merge");
+    let old = dir.join("old.flac");
+    let new = dir.join("new.flac");
+    fs::write(&new, b"c").unwrap();
+
+    let mut entries = vec![ScanEntry::new(&old)];
+    let result = merge_scan(&mut entries, vec![new.clone()]);
+    assert_eq!(result.removed, vec![old]);
+    assert_eq!(result.added, vec![new.clone()]);
+    assert_eq!(entries, vec![ScanEntry::new(&new)]);
+
+    let again = merge_scan(&mut entries, vec![new.clone()]);
+    assert!(again.added.is_empty() && again.removed.is_empty());
+
+    // a path listed twice is added once
+    let twice = dir.join("twice.flac");
+    fs::write(&twice, b"d").unwrap();
+    let result = merge_scan(&mut entries, vec![twice.clone(), new, twice.clone()]);
+    assert_eq!(result.added, vec![twice]);
+    assert_eq!(entries.len(), 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
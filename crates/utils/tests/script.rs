@@ -0,0 +1,33 @@
+use utils::{detect_script, is_roman_alphabet, Script};
+
+#[test]
+fn test_detect_script_single_scripts() {
+    assert_eq!(detect_script("Daft Punk - One More Time"), Script::Latin);
+    assert_eq!(detect_script("Beyoncé"), Script::Latin);
+    assert_eq!(detect_script("Кино"), Script::Cyrillic);
+    assert_eq!(detect_script("Βαγγέλης"), Script::Greek);
+    assert_eq!(detect_script("فيروز"), Script::Arabic);
+    assert_eq!(detect_script("坂本龍一"), Script::Cjk);
+    assert_eq!(detect_script("きゃりーぱみゅぱみゅ"), Script::Cjk);
+    assert_eq!(detect_script("방탄소년단"), Script::Cjk);
+}
+
+#[test]
+fn test_detect_script_majority_and_edge_cases() {
+    // the Cyrillic letters outnumber the Latin ones
+    assert_eq!(detect_script("Группа крови (live)"), Script::Cyrillic);
+    assert_eq!(detect_script("abcd где"), Script::Latin);
+    assert_eq!(detect_script("abc где"), Script::Mixed);
+    assert_eq!(detect_script("שלום"), Script::Mixed);
+    assert_eq!(detect_script(""), Script::Empty);
+    assert_eq!(detect_script("1999 - !!!"), Script::Empty);
+}
+
+#[test]
+fn test_is_roman_alphabet() {
+    assert!(is_roman_alphabet("Daft Punk"));
+    assert!(!is_roman_alphabet("1999"));
+    assert!(!is_roman_alphabet(""));
+    assert!(!is_roman_alphabet("Кино"));
+    assert!(!is_roman_alphabet("坂本龍一"));
+}
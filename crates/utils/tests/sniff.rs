@@ -0,0 +1,26 @@
+use utils::{collect_music_files_filtered, sniff_audio, ScanOptions, SUPPORTED_EXTENSIONS};
+
+use std::fs;
+
+#[test]
+fn test_sniff_keeps_extensionless_audio() {
+    let base = std::env::temp_dir().join(format!("utils_sniff_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    fs::create_dir_all(&base).unwrap();
+    fs::write(base.join("download"), b"ID3\x03\0\0\0\0\0\0").unwrap();
+    fs::write(base.join("flac_noext"), b"fLaC\0\0\0\x22........").unwrap();
+    fs::write(base.join("clip"), b"RIFF\x24\0\0\0WAVEfmt ").unwrap();
+    fs::write(base.join("aac"), b"\0\0\0\x20ftypM4A \0\0").unwrap();
+    fs::write(base.join("readme"), b"plain text, not audio").unwrap();
+    fs::write(base.join("short"), b"ID3").unwrap();
+
+    assert!(collect_music_files_filtered(&base, &SUPPORTED_EXTENSIONS, &ScanOptions::default()).unwrap().is_empty());
+
+    let sniffing = ScanOptions { sniff: true, ..ScanOptions::default() };
+    let found = collect_music_files_filtered(&base, &SUPPORTED_EXTENSIONS, &sniffing).unwrap();
+    assert_eq!(found, ["aac", "clip", "download", "flac_noext"].map(|n| base.join(n)));
+
+    assert!(!sniff_audio(&base.join("missing")));
+
+    fs::remove_dir_all(&base).unwrap();
+}